@@ -1,7 +1,10 @@
 #[rustversion::nightly]
 fn main() {
+    println!("cargo:rustc-check-cfg=cfg(unstable_feature)");
     println!("cargo:rustc-cfg=unstable_feature");
 }
 
 #[rustversion::not(nightly)]
-fn main() {}
+fn main() {
+    println!("cargo:rustc-check-cfg=cfg(unstable_feature)");
+}
@@ -0,0 +1,18 @@
+use syn::{Attribute, Error, LitInt, Result};
+
+/// Parses width of `#[bits(n)]` field
+pub fn parse(attrs: &[Attribute]) -> Result<Option<(LitInt, u32)>> {
+    let mut found = attrs.iter().filter(|attr| attr.path.is_ident("bits"));
+
+    let attr = match found.next() {
+        Some(attr) => attr,
+        None => return Ok(None),
+    };
+    if let Some(duplicate) = found.next() {
+        return Err(Error::new_spanned(duplicate, "duplicated bits attribute"));
+    }
+
+    let lit = attr.parse_args::<LitInt>()?;
+    let n = lit.base10_parse::<u32>()?;
+    Ok(Some((lit, n)))
+}
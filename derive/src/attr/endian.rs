@@ -1,6 +1,7 @@
 use crate::attr;
-use proc_macro2::{Ident, Span};
-use syn::{Attribute, Error, Meta, NestedMeta, Result};
+use proc_macro2::{Ident, Span, TokenStream};
+use syn::parse::ParseStream;
+use syn::{Attribute, Error, Expr, Meta, NestedMeta, Result, Token};
 
 macro_rules! ident {
     ($t:tt) => {
@@ -8,7 +9,7 @@ macro_rules! ident {
     };
 }
 
-fn determine_endian(ident: &Ident) -> Result<Ident> {
+pub fn determine_endian(ident: &Ident) -> Result<Ident> {
     match ident.to_string().as_str() {
         "_" | "ne" | "native" => Ok(ident!(NativeEndian)),
         "le" | "little" => Ok(ident!(LittleEndian)),
@@ -30,12 +31,69 @@ fn parse_endian_attr(attr: &Attribute) -> Result<Ident> {
         })
         .ok_or_else(|| Error::new_spanned(attr, "excepted endian identifier"))?;
 
-    Ok(determine_endian(ident)?)
+    Ok(ident.clone())
 }
 
 pub fn parse(attrs: &[Attribute]) -> Result<Option<Ident>> {
     attr::find(attrs, "endian")
-        .map(parse_endian_attr)
+        .map(|attr| {
+            let ident = parse_endian_attr(attr)?;
+            if ident == "runtime" {
+                return Err(Error::new_spanned(
+                    ident,
+                    "runtime endian can be used only on the type",
+                ));
+            }
+            determine_endian(&ident)
+        })
+        .transpose()
+}
+
+/// Default endian of a derived type
+pub enum TypeEndian {
+    Declared(Ident),
+    /// `#[endian(runtime)]`, the endian is passed to `write_endian` and `read_endian`
+    Runtime,
+    /// `#[endian(from = expr)]`, the endian is returned by `expr` that refers to earlier fields
+    From(Box<Expr>),
+}
+
+/// Parses `from = expr` if the attribute has it
+fn parse_from(attr: &Attribute) -> Result<Option<Expr>> {
+    //missing arguments are reported by `parse_endian_attr`
+    if let Ok(Meta::Path(_)) = attr.parse_meta() {
+        return Ok(None);
+    }
+
+    attr.parse_args_with(|input: ParseStream| {
+        if !(input.peek(syn::Ident) && input.peek2(Token![=])) {
+            input.parse::<TokenStream>()?;
+            return Ok(None);
+        }
+
+        let key = input.parse::<Ident>()?;
+        if key != "from" {
+            return Err(Error::new_spanned(key, "expected `from = expr`"));
+        }
+        input.parse::<Token![=]>()?;
+        input.parse().map(Some)
+    })
+}
+
+pub fn parse_type(attrs: &[Attribute]) -> Result<Option<TypeEndian>> {
+    attr::find(attrs, "endian")
+        .map(|attr| {
+            if let Some(expr) = parse_from(attr)? {
+                return Ok(TypeEndian::From(Box::new(expr)));
+            }
+
+            let ident = parse_endian_attr(attr)?;
+            if ident == "runtime" {
+                Ok(TypeEndian::Runtime)
+            } else {
+                determine_endian(&ident).map(TypeEndian::Declared)
+            }
+        })
         .transpose()
 }
 
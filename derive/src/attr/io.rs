@@ -0,0 +1,485 @@
+use crate::attr::endian;
+use proc_macro2::Ident;
+use quote::ToTokens;
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{
+    parenthesized, parse_quote_spanned, Attribute, Error, Expr, Lit, LitStr, Path, Result, Token,
+    Type,
+};
+
+/// Arguments of `#[io(...)]` attributes placed on a field
+#[derive(Default)]
+pub struct FieldArgs {
+    /// Type of the length prefix written before a collection
+    pub len: Option<Type>,
+    /// Endian of the length prefix, the field endian is used if omitted
+    pub len_endian: Option<Ident>,
+    /// Expression with number of elements in a collection, usually an earlier field
+    pub count: Option<Expr>,
+    /// String is terminated by NUL byte
+    pub cstr: Option<Ident>,
+    /// String occupies exactly this number of bytes
+    pub fixed: Option<Expr>,
+    /// Byte that fills the rest of a fixed string, zero if omitted
+    pub pad: Option<Expr>,
+    /// String is encoded as UTF-16 in the field endian
+    pub utf16: Option<Ident>,
+    /// Unpaired surrogates of UTF-16 string are replaced instead of rejected
+    pub lossy: Option<Ident>,
+    /// Value which is written instead of `None`, no presence flag is used then
+    pub sentinel: Option<Expr>,
+    /// Integer is written with variable length: `leb128` (default), `zigzag` or `vlq`
+    pub varint: Option<Ident>,
+    /// Number of zero bytes before the field
+    pub pad_before: Option<Expr>,
+    /// Number of zero bytes after the field
+    pub pad_after: Option<Expr>,
+    /// Field starts at an offset from the start of the type which is a multiple of this number
+    pub align: Option<Expr>,
+    /// Context that is passed to `IoWith` implementation of the field type
+    pub args: Option<Punctuated<Expr, Token![,]>>,
+    /// Field is not written, on read it's initialized with this expression
+    pub skip: Option<Expr>,
+    /// `Option<T>` is present only if this expression is true, no presence flag is used then
+    pub condition: Option<Expr>,
+    /// Predicates which are checked after the field is read
+    pub asserts: Vec<Assert>,
+    /// Functions that convert the field from and into its `wire` type
+    pub map: Option<Map>,
+    /// Type which is actually written instead of the field
+    pub wire: Option<Type>,
+    /// Module with `read` and `write` functions which are used instead of `Io`
+    pub with: Option<Path>,
+    /// Expression which is written instead of the stored value
+    pub calc: Option<Expr>,
+    /// Algorithm of checksum which is written instead of the stored value and verified on read
+    pub checksum: Option<Path>,
+    /// Range of fields that the checksum is computed over, all previous fields if omitted
+    pub over: Option<LitStr>,
+    /// Number of bytes that the field occupies
+    pub size: Option<Expr>,
+    /// What to do with bytes which are left after the sized field: `skip` (default) or `error`
+    pub trailing: Option<Ident>,
+    /// Field whose length in bytes is written instead of the stored value
+    pub len_of: Option<Ident>,
+}
+
+impl FieldArgs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut args = Self::default();
+        let mut layouts = Vec::new();
+        let mut keys = Vec::new();
+
+        parse_args(attrs, |key, input| {
+            keys.push(key.clone());
+            if matches!(
+                key.to_string().as_str(),
+                "len"
+                    | "count"
+                    | "cstr"
+                    | "fixed"
+                    | "sentinel"
+                    | "varint"
+                    | "if"
+                    | "map"
+                    | "try_map"
+                    | "with"
+                    | "len_of"
+            ) {
+                layouts.push(key.clone());
+            }
+
+            match key.to_string().as_str() {
+                "len" => set(&mut args.len, key, value(input)?),
+                "len_endian" => {
+                    let ident = value::<Ident>(input)?;
+                    let mut endian = endian::determine_endian(&ident)?;
+                    endian.set_span(ident.span());
+                    set(&mut args.len_endian, key, endian)
+                }
+                "count" => set(&mut args.count, key, value(input)?),
+                "cstr" => set(&mut args.cstr, key, key.clone()),
+                "fixed" => set(&mut args.fixed, key, value(input)?),
+                "pad" => set(&mut args.pad, key, value(input)?),
+                "utf16" => set(&mut args.utf16, key, key.clone()),
+                "lossy" => set(&mut args.lossy, key, key.clone()),
+                "sentinel" => set(&mut args.sentinel, key, value(input)?),
+                "if" => set(&mut args.condition, key, value(input)?),
+                "map" | "try_map" => {
+                    let mut map = value::<Map>(input)?;
+                    map.fallible = key == "try_map";
+                    set(&mut args.map, key, map)
+                }
+                "wire" => set(&mut args.wire, key, value(input)?),
+                "with" => set(&mut args.with, key, value(input)?),
+                "calc" => set(&mut args.calc, key, value(input)?),
+                "checksum" => set(&mut args.checksum, key, value(input)?),
+                "over" => set(&mut args.over, key, value(input)?),
+                "size" => set(&mut args.size, key, value(input)?),
+                "len_of" => set(&mut args.len_of, key, value(input)?),
+                "trailing" => {
+                    let trailing = value::<Ident>(input)?;
+                    if !matches!(trailing.to_string().as_str(), "skip" | "error") {
+                        return Err(Error::new_spanned(
+                            trailing,
+                            "expected handling of trailing bytes: skip or error",
+                        ));
+                    }
+                    set(&mut args.trailing, key, trailing)
+                }
+                "assert" => {
+                    args.asserts.push(value(input)?);
+                    Ok(())
+                }
+                "pad_before" => set(&mut args.pad_before, key, value(input)?),
+                "pad_after" => set(&mut args.pad_after, key, value(input)?),
+                "align" => {
+                    let align = value::<Expr>(input)?;
+                    if let Expr::Lit(syn::ExprLit {
+                        lit: Lit::Int(int), ..
+                    }) = &align
+                    {
+                        if int.base10_parse::<u64>()? == 0 {
+                            return Err(Error::new_spanned(int, "align must be greater than zero"));
+                        }
+                    }
+                    set(&mut args.align, key, align)
+                }
+                "args" => {
+                    let content;
+                    parenthesized!(content in input);
+                    set(&mut args.args, key, content.parse_terminated(Expr::parse)?)
+                }
+                "skip" => {
+                    let default = if input.peek(Token![=]) {
+                        value::<Expr>(input)?
+                    } else {
+                        parse_quote_spanned!(key.span()=> ::std::default::Default::default())
+                    };
+                    set(&mut args.skip, key, default)
+                }
+                "varint" => {
+                    let encoding = if input.peek(Token![=]) {
+                        value::<Ident>(input)?
+                    } else {
+                        Ident::new("leb128", key.span())
+                    };
+                    if !matches!(encoding.to_string().as_str(), "leb128" | "zigzag" | "vlq") {
+                        return Err(Error::new_spanned(
+                            encoding,
+                            "expected one of varint encodings: leb128, zigzag, vlq",
+                        ));
+                    }
+                    set(&mut args.varint, key, encoding)
+                }
+                _ => Err(Error::new_spanned(key, "unknown io argument")),
+            }
+        })?;
+
+        match (&args.map, &args.wire) {
+            (Some(map), None) => {
+                return Err(Error::new_spanned(&map.read, "map requires wire type"));
+            }
+            (None, Some(wire)) => {
+                return Err(Error::new_spanned(wire, "wire requires map or try_map"));
+            }
+            _ => {}
+        }
+        if args.skip.is_some() {
+            if let Some(key) = keys.iter().find(|key| *key != "skip") {
+                return Err(Error::new_spanned(
+                    key,
+                    format!("skip and {} cannot be used together", key),
+                ));
+            }
+        }
+        if let (None, Some(trailing)) = (&args.size, &args.trailing) {
+            return Err(Error::new_spanned(trailing, "trailing requires size"));
+        }
+        if let (None, Some(over)) = (&args.checksum, &args.over) {
+            return Err(Error::new_spanned(over, "over requires checksum"));
+        }
+        if let (Some(_), Some(calc)) = (&args.checksum, &args.calc) {
+            return Err(Error::new_spanned(
+                calc,
+                "checksum and calc cannot be used together",
+            ));
+        }
+        if let Some(len_of) = &args.len_of {
+            let computed = match (&args.calc, &args.checksum) {
+                (Some(_), _) => Some("calc"),
+                (_, Some(_)) => Some("checksum"),
+                _ => None,
+            };
+            if let Some(key) = computed {
+                return Err(Error::new_spanned(
+                    len_of,
+                    format!("len_of and {} cannot be used together", key),
+                ));
+            }
+        }
+        if let (None, Some(endian)) = (&args.len, &args.len_endian) {
+            return Err(Error::new_spanned(endian, "len_endian requires len"));
+        }
+        if let (None, Some(pad)) = (&args.fixed, &args.pad) {
+            return Err(Error::new_spanned(pad, "pad requires fixed"));
+        }
+        if let Some(utf16) = &args.utf16 {
+            if args.len.is_none() && args.cstr.is_none() {
+                return Err(Error::new_spanned(utf16, "utf16 requires len or cstr"));
+            }
+        }
+        if let (None, Some(lossy)) = (&args.utf16, &args.lossy) {
+            return Err(Error::new_spanned(lossy, "lossy requires utf16"));
+        }
+        if args.args.is_some() {
+            let unsupported = layouts.iter().find(|key| {
+                matches!(
+                    key.to_string().as_str(),
+                    "cstr" | "fixed" | "varint" | "with"
+                )
+            });
+            if let Some(key) = unsupported {
+                return Err(Error::new_spanned(
+                    key,
+                    format!("args and {} cannot be used together", key),
+                ));
+            }
+        }
+        if let [first, second, ..] = layouts.as_slice() {
+            return Err(Error::new_spanned(
+                second,
+                format!("{} and {} cannot be used together", first, second),
+            ));
+        }
+
+        Ok(args)
+    }
+}
+
+/// Arguments of `#[io(...)]` attributes placed on a struct or enum
+#[derive(Default)]
+pub struct ContainerArgs {
+    /// Backing integer of `#[bits(n)]` fields
+    pub bitfield: Option<Type>,
+    /// Order of `#[bits(n)]` fields inside of the backing integer: `msb` (default) or `lsb`
+    pub bit_order: Option<Ident>,
+    /// Parameters of `IoWith` context, the type is derived without `Io` if it's present
+    pub context: Option<Punctuated<ContextParam, Token![,]>>,
+    /// Byte string or suffixed integer which is placed before the fields
+    pub magic: Option<Lit>,
+    /// Padding of fields must consist of zeros, otherwise it's skipped on read
+    pub strict_padding: Option<Ident>,
+    /// Predicates which are checked after all fields are read
+    pub asserts: Vec<Assert>,
+    /// Assertions of the type and its fields are checked before write too
+    pub assert_on_write: Option<Ident>,
+}
+
+/// `(read, write)` functions in `#[io(map = ...)]` or `#[io(try_map = ...)]`
+pub struct Map {
+    /// Converts the wire value into the field
+    pub read: Expr,
+    /// Converts reference to the field into the wire value
+    pub write: Expr,
+    /// Functions return `Result`, errors are converted into `std::io::Error`
+    pub fallible: bool,
+}
+
+impl IoValue for Map {
+    fn parse_value(input: ParseStream) -> Result<Self> {
+        match input.parse::<Expr>()? {
+            Expr::Tuple(tuple) if tuple.elems.len() == 2 => Ok(Self {
+                read: tuple.elems[0].clone(),
+                write: tuple.elems[1].clone(),
+                fallible: false,
+            }),
+            expr => Err(Error::new_spanned(
+                expr,
+                "expected `(read, write)` functions",
+            )),
+        }
+    }
+}
+
+/// `expr` or `expr, "message"` in `#[io(assert = ...)]`
+pub struct Assert {
+    pub condition: Expr,
+    pub message: Option<LitStr>,
+}
+
+impl Assert {
+    /// Message of the error, the predicate itself is used if it's omitted
+    pub fn message(&self) -> String {
+        match &self.message {
+            Some(message) => message.value(),
+            None => format!("assertion failed: {}", self.condition.to_token_stream()),
+        }
+    }
+}
+
+impl IoValue for Assert {
+    fn parse_value(input: ParseStream) -> Result<Self> {
+        let condition = input.parse()?;
+        let message = if input.peek(Token![,]) && input.peek2(LitStr) {
+            input.parse::<Token![,]>()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(Self { condition, message })
+    }
+}
+
+/// `name: Type` in `#[io(context(...))]`
+pub struct ContextParam {
+    pub name: Ident,
+    pub ty: Type,
+}
+
+impl Parse for ContextParam {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![:]>()?;
+        Ok(Self {
+            name,
+            ty: input.parse()?,
+        })
+    }
+}
+
+impl ContainerArgs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut args = Self::default();
+
+        parse_args(attrs, |key, input| match key.to_string().as_str() {
+            "bitfield" => set(&mut args.bitfield, key, value(input)?),
+            "bit_order" => {
+                let order = value::<Ident>(input)?;
+                if !matches!(order.to_string().as_str(), "msb" | "lsb") {
+                    return Err(Error::new_spanned(order, "expected bit order: msb or lsb"));
+                }
+                set(&mut args.bit_order, key, order)
+            }
+            "magic" => {
+                let magic = value::<Lit>(input)?;
+                match &magic {
+                    Lit::ByteStr(_) => {}
+                    Lit::Int(int) if !int.suffix().is_empty() => {}
+                    Lit::Int(int) => {
+                        return Err(Error::new_spanned(
+                            int,
+                            "integer magic must have a type suffix, like 0xCAFEBABE_u32",
+                        ))
+                    }
+                    _ => {
+                        return Err(Error::new_spanned(
+                            magic,
+                            "magic must be a byte string or an integer",
+                        ))
+                    }
+                }
+                set(&mut args.magic, key, magic)
+            }
+            "strict_padding" => set(&mut args.strict_padding, key, key.clone()),
+            "assert" => {
+                args.asserts.push(value(input)?);
+                Ok(())
+            }
+            "assert_on_write" => set(&mut args.assert_on_write, key, key.clone()),
+            "context" => {
+                let content;
+                parenthesized!(content in input);
+                set(
+                    &mut args.context,
+                    key,
+                    content.parse_terminated(ContextParam::parse)?,
+                )
+            }
+            _ => Err(Error::new_spanned(key, "unknown io argument")),
+        })?;
+
+        if let (None, Some(order)) = (&args.bitfield, &args.bit_order) {
+            return Err(Error::new_spanned(order, "bit_order requires bitfield"));
+        }
+
+        Ok(args)
+    }
+}
+
+/// Calls `f` for each `key` of `#[io(key, key = value, ...)]` attributes,
+/// `f` must consume the value if the key has one
+fn parse_args<F>(attrs: &[Attribute], mut f: F) -> Result<()>
+where
+    F: FnMut(&Ident, ParseStream) -> Result<()>,
+{
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("io")) {
+        attr.parse_args_with(|input: ParseStream| {
+            while !input.is_empty() {
+                let key = Ident::parse_any(input)?;
+                f(&key, input)?;
+
+                if !input.is_empty() {
+                    input.parse::<Token![,]>()?;
+                }
+            }
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
+
+fn value<T: IoValue>(input: ParseStream) -> Result<T> {
+    input.parse::<Token![=]>()?;
+    T::parse_value(input)
+}
+
+fn set<T>(slot: &mut Option<T>, key: &Ident, value: T) -> Result<()> {
+    match slot.replace(value) {
+        Some(_) => Err(Error::new_spanned(key, "duplicated io argument")),
+        None => Ok(()),
+    }
+}
+
+/// Right-hand side of `key = value` argument
+trait IoValue: Sized {
+    fn parse_value(input: ParseStream) -> Result<Self>;
+}
+
+impl IoValue for Ident {
+    fn parse_value(input: ParseStream) -> Result<Self> {
+        Ident::parse_any(input)
+    }
+}
+
+impl IoValue for Expr {
+    fn parse_value(input: ParseStream) -> Result<Self> {
+        input.parse()
+    }
+}
+
+impl IoValue for Lit {
+    fn parse_value(input: ParseStream) -> Result<Self> {
+        input.parse()
+    }
+}
+
+impl IoValue for LitStr {
+    fn parse_value(input: ParseStream) -> Result<Self> {
+        input.parse()
+    }
+}
+
+impl IoValue for Path {
+    fn parse_value(input: ParseStream) -> Result<Self> {
+        input.parse()
+    }
+}
+
+impl IoValue for Type {
+    fn parse_value(input: ParseStream) -> Result<Self> {
+        input.parse()
+    }
+}
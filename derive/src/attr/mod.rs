@@ -1,6 +1,8 @@
 use syn::Attribute;
 
+pub mod bits;
 pub mod endian;
+pub mod io;
 
 fn only_one<I: Iterator<Item = T>, T>(mut it: I) -> Option<T> {
     match (it.next(), it.next()) {
@@ -0,0 +1,205 @@
+use crate::attr::{self, io::ContainerArgs};
+use crate::fields;
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::quote;
+use syn::{Error, Field, Fields, Result, Type};
+
+/// Field of a derived type, either on its own or packed together with its neighbours
+pub enum Item<'a> {
+    Field(usize, &'a Field),
+    Bitfield(Unit<'a>),
+}
+
+impl Item<'_> {
+    /// Returns names which the item binds
+    pub fn bindings(&self) -> Vec<Ident> {
+        match self {
+            Item::Field(i, field) => vec![fields::binding(*i, field)],
+            Item::Bitfield(unit) => unit.bindings().cloned().collect(),
+        }
+    }
+}
+
+/// Consecutive `#[bits(n)]` fields that fill exactly one backing integer
+pub struct Unit<'a> {
+    backing: &'a Type,
+    width: u32,
+    members: Vec<Member<'a>>,
+}
+
+struct Member<'a> {
+    binding: Ident,
+    field: &'a Field,
+    n: u32,
+    shift: u32,
+}
+
+/// Splits fields into items, consecutive `#[bits(n)]` fields are packed into units
+pub fn group<'a>(fields: &'a Fields, container: &'a ContainerArgs) -> Result<Vec<Item<'a>>> {
+    let backing = match &container.bitfield {
+        Some(backing) => Some((backing, width(backing)?)),
+        None => None,
+    };
+    let msb = match &container.bit_order {
+        Some(order) => order == "msb",
+        None => true,
+    };
+
+    let mut items = Vec::new();
+    let mut unit: Option<Unit> = None;
+    let mut filled = 0;
+
+    for (i, field) in fields.iter().enumerate() {
+        let (lit, n) = match attr::bits::parse(&field.attrs)? {
+            Some(bits) => bits,
+            None => {
+                if let Some(unit) = &unit {
+                    return Err(unfilled(unit, filled));
+                }
+                items.push(Item::Field(i, field));
+                continue;
+            }
+        };
+
+        let (ty, width) = backing.ok_or_else(|| {
+            Error::new_spanned(&lit, "bits requires #[io(bitfield = ...)] on the type")
+        })?;
+        if n == 0 || n > width {
+            return Err(Error::new_spanned(
+                &lit,
+                format!("bits must be in range 1..={}", width),
+            ));
+        }
+        if let Some(attr) = attr::find(&field.attrs, "endian") {
+            return Err(Error::new_spanned(attr, "bits use endian of the bitfield"));
+        }
+        if let Some(attr) = field.attrs.iter().find(|attr| attr.path.is_ident("io")) {
+            return Err(Error::new_spanned(
+                attr,
+                "io arguments cannot be used with bits",
+            ));
+        }
+        if filled + n > width {
+            return Err(Error::new_spanned(
+                &lit,
+                format!(
+                    "field does not fit into the rest of bitfield, {} of {} bits are left",
+                    width - filled,
+                    width
+                ),
+            ));
+        }
+
+        let shift = if msb { width - filled - n } else { filled };
+        unit.get_or_insert_with(|| Unit {
+            backing: ty,
+            width,
+            members: Vec::new(),
+        })
+        .members
+        .push(Member {
+            binding: fields::binding(i, field),
+            field,
+            n,
+            shift,
+        });
+
+        filled += n;
+        if filled == width {
+            items.extend(unit.take().map(Item::Bitfield));
+            filled = 0;
+        }
+    }
+
+    match &unit {
+        Some(unit) => Err(unfilled(unit, filled)),
+        None => Ok(items),
+    }
+}
+
+fn width(ty: &Type) -> Result<u32> {
+    let ident = match ty {
+        Type::Path(path) if path.qself.is_none() => path.path.get_ident(),
+        _ => None,
+    };
+
+    match ident.map(Ident::to_string).as_deref() {
+        Some("u8") => Ok(8),
+        Some("u16") => Ok(16),
+        Some("u32") => Ok(32),
+        Some("u64") => Ok(64),
+        Some("u128") => Ok(128),
+        _ => Err(Error::new_spanned(
+            ty,
+            "bitfield must be one of u8, u16, u32, u64, u128",
+        )),
+    }
+}
+
+fn unfilled(unit: &Unit, filled: u32) -> Error {
+    let last = unit.members.last().expect("unit is never empty");
+    Error::new_spanned(
+        last.field,
+        format!(
+            "bits must fill the whole bitfield, only {} of {} bits are used",
+            filled, unit.width
+        ),
+    )
+}
+
+fn bits() -> Ident {
+    Ident::new("bits", Span::mixed_site())
+}
+
+impl Unit<'_> {
+    /// Generates writing of members that are bound by references, like the rest of fields
+    pub fn write(&self, endian: &TokenStream) -> TokenStream {
+        let (w, bits, backing) = (fields::writer(), bits(), self.backing);
+
+        let packs = self.members.iter().map(|member| {
+            let (name, n, shift) = (&member.binding, member.n, member.shift);
+            let label = name.to_string();
+            quote! {
+                #bits |= ::endiannezz::internal::pack_field(#name, #n, #label)? << #shift;
+            }
+        });
+
+        quote! {{
+            let mut #bits: u128 = 0;
+            #(#packs)*
+            #endian::write::<#backing, _>(#bits as #backing, &mut #w)?;
+        }}
+    }
+
+    /// Generates reading of the backing integer and binding of each member to a local
+    pub fn read(&self, endian: &TokenStream) -> TokenStream {
+        let (r, bits, backing) = (fields::reader(), bits(), self.backing);
+
+        let unpacks = self.members.iter().map(|member| {
+            let (name, ty, n, shift) = (&member.binding, &member.field.ty, member.n, member.shift);
+            let mask = Literal::u128_unsuffixed(u128::MAX >> (128 - n));
+            let label = name.to_string();
+            quote! {
+                let #name = ::endiannezz::internal::unpack_field::<#ty>(
+                    (#bits >> #shift) & #mask,
+                    #n,
+                    #label,
+                )?;
+            }
+        });
+
+        quote! {
+            let #bits = u128::from(#endian::read::<#backing, _>(&mut #r)?);
+            #(#unpacks)*
+        }
+    }
+
+    pub fn bindings(&self) -> impl Iterator<Item = &Ident> {
+        self.members.iter().map(|member| &member.binding)
+    }
+
+    /// Backing integer that takes more than one byte depends on the byte order
+    pub fn multibyte(&self) -> bool {
+        self.width > 8
+    }
+}
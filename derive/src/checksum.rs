@@ -0,0 +1,172 @@
+use crate::attr::io::FieldArgs;
+use crate::bitfield::Item;
+use crate::fields;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::{Error, Expr, ExprRange, Fields, Path, RangeLimits, Result};
+
+/// Checksum field along with the fields it's computed over
+pub struct Coverage {
+    /// Local which holds state of the algorithm
+    pub state: Ident,
+    pub algorithm: TokenStream,
+    /// Binding of the checksum field itself
+    pub field: Ident,
+    /// Bindings of the fields which are digested
+    pub covered: Vec<Ident>,
+}
+
+/// Finds fields with `#[io(checksum = ...)]` and resolves ranges of their `over` arguments
+pub fn coverages(fields: &Fields, items: &[Item]) -> Result<Vec<Coverage>> {
+    let bindings = fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| fields::binding(i, field))
+        .collect::<Vec<_>>();
+
+    let mut coverages = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let args = FieldArgs::parse(&field.attrs)?;
+        let checksum = match &args.checksum {
+            Some(checksum) => checksum,
+            None => continue,
+        };
+
+        let (start, end) = match &args.over {
+            Some(over) => {
+                let range = over.parse::<ExprRange>()?;
+                let position = |bound: &Option<Box<Expr>>| match bound.as_deref() {
+                    Some(Expr::Path(path)) => bindings
+                        .iter()
+                        .position(|binding| path.path.is_ident(binding))
+                        .map(Some)
+                        .ok_or_else(|| Error::new_spanned(over, "over must refer to fields")),
+                    Some(_) => Err(Error::new_spanned(over, "over must refer to fields")),
+                    None => Ok(None),
+                };
+
+                let start = position(&range.from)?.unwrap_or(0);
+                let end = match (position(&range.to)?, range.limits) {
+                    (Some(end), RangeLimits::Closed(_)) => end + 1,
+                    (Some(end), RangeLimits::HalfOpen(_)) => end,
+                    (None, _) => i,
+                };
+                if end > i {
+                    return Err(Error::new_spanned(
+                        over,
+                        "checksum must follow the fields it's computed over",
+                    ));
+                }
+                if start >= end {
+                    return Err(Error::new_spanned(over, "checksum must cover some fields"));
+                }
+                (start, end)
+            }
+            None => (0, i),
+        };
+
+        let covered = bindings[start..end].to_vec();
+        for item in items {
+            let item_bindings = item.bindings();
+            let inside = item_bindings
+                .iter()
+                .filter(|binding| covered.contains(binding))
+                .count();
+            if inside != 0 && inside != item_bindings.len() {
+                return Err(Error::new_spanned(
+                    args.over.to_token_stream(),
+                    "checksum cannot cover only a part of bitfield",
+                ));
+            }
+        }
+
+        coverages.push(Coverage {
+            state: Ident::new(&format!("checksum_{}", i), Span::mixed_site()),
+            algorithm: algorithm(checksum),
+            field: bindings[i].clone(),
+            covered,
+        });
+    }
+    Ok(coverages)
+}
+
+/// Built-in algorithms are named in lowercase, anything else is a path to `Checksum` type
+fn algorithm(checksum: &Path) -> TokenStream {
+    let builtin = match checksum.get_ident().map(Ident::to_string).as_deref() {
+        Some("crc32") => "Crc32",
+        Some("adler32") => "Adler32",
+        Some("sum8") => "Sum8",
+        Some("sum16") => "Sum16",
+        Some("sum32") => "Sum32",
+        _ => return quote!(#checksum),
+    };
+    let builtin = Ident::new(builtin, checksum.segments[0].ident.span());
+    quote!(::endiannezz::checksum::#builtin)
+}
+
+/// Declares states of all checksums, they are updated while covered fields pass through
+pub fn states(coverages: &[Coverage]) -> TokenStream {
+    let states = coverages.iter().map(|coverage| {
+        let (state, algorithm) = (&coverage.state, &coverage.algorithm);
+        quote! {
+            let mut #state = <#algorithm as ::std::default::Default>::default();
+        }
+    });
+    quote!(#(#states)*)
+}
+
+/// Checksums which digest the item
+pub fn covering<'c>(coverages: &'c [Coverage], item: &Item) -> Vec<&'c Coverage> {
+    let bindings = item.bindings();
+    coverages
+        .iter()
+        .filter(|coverage| coverage.covered.contains(&bindings[0]))
+        .collect()
+}
+
+/// Checksum which is stored in the field
+pub fn stored_in<'c>(coverages: &'c [Coverage], field: &Ident) -> Option<&'c Coverage> {
+    coverages.iter().find(|coverage| coverage.field == *field)
+}
+
+/// Shadows the writer with adapters that digest everything the item writes
+pub fn write(coverages: &[&Coverage], item: TokenStream) -> TokenStream {
+    if coverages.is_empty() {
+        return item;
+    }
+
+    let w = fields::writer();
+    let states = coverages.iter().map(|coverage| &coverage.state);
+    quote! {{
+        #(
+            let mut #w = ::endiannezz::checksum::ChecksumWriter::with_checksum(
+                &mut #w,
+                &mut #states,
+            );
+        )*
+        #item
+    }}
+}
+
+/// Shadows the reader with adapters that digest everything the item reads,
+/// bindings of the item are moved out of the block
+pub fn read(coverages: &[&Coverage], item: TokenStream, bindings: &[Ident]) -> TokenStream {
+    if coverages.is_empty() {
+        return item;
+    }
+
+    let r = fields::reader();
+    let states = coverages.iter().map(|coverage| &coverage.state);
+    quote! {
+        let (#(#bindings,)*) = {
+            #(
+                let mut #r = ::endiannezz::checksum::ChecksumReader::with_checksum(
+                    &mut #r,
+                    &mut #states,
+                );
+            )*
+            #item
+            (#(#bindings,)*)
+        };
+    }
+}
@@ -1,92 +1,1215 @@
-use crate::attr::endian;
-use proc_macro2::{Ident, TokenStream};
-use quote::{format_ident, quote};
-use syn::{Fields, Result, Type};
+use crate::attr::io::{Assert, ContainerArgs, FieldArgs};
+use crate::attr::{self, endian};
+use crate::bitfield::{self, Item};
+use crate::checksum::{self, Coverage};
+use crate::length::{self, Length};
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::{format_ident, quote, ToTokens};
+use syn::punctuated::Punctuated;
+use syn::{
+    Block, Error, Expr, Field, Fields, GenericArgument, PathArguments, Result, Stmt, Token, Type,
+};
 
-pub fn write<Named, Unnamed>(
+/// Name of the reader inside of derived `read`, invisible for user expressions
+pub fn reader() -> Ident {
+    Ident::new("r", Span::mixed_site())
+}
+
+/// Name of the writer inside of derived `write`, invisible for user expressions
+pub fn writer() -> Ident {
+    Ident::new("w", Span::mixed_site())
+}
+
+/// Name of the element of a collection or option, invisible for user expressions
+fn element() -> Ident {
+    Ident::new("item", Span::mixed_site())
+}
+
+/// Name of the context parameter of derived `write_with` and `read_with`
+pub fn context_param() -> Ident {
+    Ident::new("ctx", Span::mixed_site())
+}
+
+/// Name of the endian parameter of derived `write_endian` and `read_endian`
+pub fn endian_param() -> Ident {
+    Ident::new("E", Span::mixed_site())
+}
+
+/// Endian of fields that don't have their own `#[endian]` attribute
+#[derive(Clone, Copy)]
+pub enum DefaultEndian<'a> {
+    /// Endian declared on the type, used by derived `write` and `read`
+    Declared(&'a Ident),
+    /// Endian passed to derived `write_endian` and `read_endian` instead of the declared one
+    Generic,
+    /// Endian that is returned by the expression of `#[endian(from = expr)]`, fields before
+    /// the byte order mark declare their own unless their encoding doesn't depend on it
+    From(&'a Expr),
+    /// Endian that was chosen by the byte order mark
+    Marked(&'a Ident),
+}
+
+impl DefaultEndian<'_> {
+    pub fn path(self) -> TokenStream {
+        match self {
+            DefaultEndian::Declared(endian) | DefaultEndian::Marked(endian) => {
+                quote!(::endiannezz::#endian)
+            }
+            DefaultEndian::Generic => {
+                let param = endian_param();
+                quote!(#param)
+            }
+            DefaultEndian::From(_) => quote!(::endiannezz::NativeEndian),
+        }
+    }
+
+    /// Returns `true` if nested `Io` types get this endian instead of their own default
+    fn forced(self) -> bool {
+        matches!(self, DefaultEndian::Generic | DefaultEndian::Marked(_))
+    }
+}
+
+/// How values of a field are written and read when it comes down to a single type
+struct Dispatch {
+    /// Endian of the field, resolved from its attribute and the default one
+    path: TokenStream,
+    /// Nested `Io` types get this endian instead of their own default
+    forced: bool,
+    /// Context of `#[io(args(...))]`, nested types are dispatched to `IoWith` if it's present
+    args: Option<TokenStream>,
+}
+
+impl ToTokens for Dispatch {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.path.to_tokens(tokens);
+    }
+}
+
+/// Generates writing of fields that are bound by references according to [`make_patterns`]
+///
+/// Lengths of `#[io(len_of = ...)]` are patched by seeking back if `seek` is set,
+/// otherwise the measured fields are buffered
+pub fn write(
     fields: &Fields,
-    access_named: Named,
-    access_unnamed: Unnamed,
-    default_endian: &Ident,
-) -> Result<TokenStream>
-where
-    Named: Fn(Option<&Ident>) -> TokenStream,
-    Unnamed: Fn(usize) -> TokenStream,
-{
+    default_endian: DefaultEndian,
+    container: &ContainerArgs,
+    seek: bool,
+) -> Result<TokenStream> {
+    let items = bitfield::group(fields, container)?;
+
+    //computed values shadow the stored ones, so other fields and assertions see them too
+    let mut calcs = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        if let Some(calc) = FieldArgs::parse(&field.attrs)?.calc {
+            let (name, ty) = (binding(i, field), &field.ty);
+            calcs.push(quote!(let #name: &#ty = &(#calc);));
+        }
+    }
+
+    //everything is checked up front, so nothing is written if the value is invalid
+    let checks = match &container.assert_on_write {
+        Some(_) => {
+            let mut asserts = Vec::new();
+            for field in fields {
+                asserts.push(check(
+                    &FieldArgs::parse(&field.attrs)?.asserts,
+                    "InvalidInput",
+                    |condition| quote!(#condition),
+                ));
+            }
+            asserts.push(check(
+                &container.asserts,
+                "InvalidInput",
+                |condition| quote!(#condition),
+            ));
+            quote!(#(#asserts)*)
+        }
+        None => TokenStream::new(),
+    };
+
+    let checksums = checksum::coverages(fields, &items)?;
+    let states = checksum::states(&checksums);
+
+    let lengths = length::lengths(fields, &items, &checksums, seek)?;
+    let locals = length::locals(&lengths);
+
+    let write = write_section(&items, default_endian, &checksums, &lengths)?;
+    Ok(quote!(#(#calcs)* #checks #states #locals #write))
+}
+
+/// Whether some of fields are aligned, then the position from the start of the type is tracked
+pub fn aligned(fields: &Fields) -> Result<bool> {
+    for field in fields {
+        if FieldArgs::parse(&field.attrs)?.align.is_some() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Generates writing of items, the ones after byte order mark are written for each byte order
+fn write_section(
+    items: &[Item],
+    default_endian: DefaultEndian,
+    checksums: &[Coverage],
+    lengths: &[Length],
+) -> Result<TokenStream> {
+    let (items, marked) = split_at_mark(items, default_endian)?;
+
+    let derived = items
+        .iter()
+        .map(|item| {
+            let write = match item {
+                Item::Field(i, field) => {
+                    let name = binding(*i, field);
+                    let args = FieldArgs::parse(&field.attrs)?;
+                    let (before, after) = write_padding(&args);
+
+                    //computed checksum shadows the stored one like `calc` does
+                    let computed = checksum::stored_in(checksums, &name).map(|coverage| {
+                        let (state, ty) = (&coverage.state, &field.ty);
+                        quote! {
+                            let #name: &#ty = &::endiannezz::checksum::Checksum::finish(&#state);
+                        }
+                    });
+
+                    //the length of the measured field is its size, so `size` only limits reading
+                    let write = write_field(&name, field, default_endian)?;
+                    let mut write = match lengths.iter().find(|length| length.body == name) {
+                        Some(length) => length::write_body(length, default_endian, write)?,
+                        None => write_sized(&name, &args, write),
+                    };
+                    if let Some(length) = lengths.iter().find(|length| length.field == name) {
+                        write = length::write_len(length, items, default_endian, write)?;
+                    }
+                    quote! {{
+                        #computed
+                        #before
+                        #write
+                        #after
+                    }}
+                }
+                Item::Bitfield(unit) => unit.write(&default_endian.path()),
+            };
+            Ok(checksum::write(&checksum::covering(checksums, item), write))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let rest = match marked {
+        Some((mark, rest)) => dispatch(&quote!(#mark), |endian| {
+            write_section(rest, DefaultEndian::Marked(endian), checksums, lengths)
+        })?,
+        None => TokenStream::new(),
+    };
+
+    Ok(quote!(#(#derived)* #rest))
+}
+
+/// Generates block that reads fields one by one and builds value using `constructor`
+///
+/// Each field is bound to a local, so expressions in attributes can refer to earlier fields
+pub fn read(
+    fields: &Fields,
+    constructor: TokenStream,
+    default_endian: DefaultEndian,
+    container: &ContainerArgs,
+) -> Result<TokenStream> {
+    let patterns = make_patterns(fields);
+    let items = bitfield::group(fields, container)?;
+
+    let bindings = items.iter().flat_map(Item::bindings).collect::<Vec<_>>();
+    let checks = check(&container.asserts, "InvalidData", |condition| {
+        with_refs(&bindings, condition)
+    });
+
+    let checksums = checksum::coverages(fields, &items)?;
+    let states = checksum::states(&checksums);
+
+    let read = read_section(
+        &items,
+        Vec::new(),
+        default_endian,
+        container.strict_padding.is_some(),
+        &checksums,
+        &quote!({
+            #checks
+            #constructor #patterns
+        }),
+    )?;
+    Ok(quote!({
+        #states
+        #read
+    }))
+}
+
+/// Generates reading of items, the ones after byte order mark are read for each byte order
+fn read_section(
+    items: &[Item],
+    mut previous: Vec<Ident>,
+    default_endian: DefaultEndian,
+    strict: bool,
+    checksums: &[Coverage],
+    constructed: &TokenStream,
+) -> Result<TokenStream> {
+    let (items, marked) = split_at_mark(items, default_endian)?;
     let mut derived = Vec::new();
 
-    match fields {
-        Fields::Named(fields) => {
-            for field in &fields.named {
-                let accessor = access_named(field.ident.as_ref());
-                let attribute = endian::parse(&field.attrs)?;
-                let endian =
-                    endian::choice(field.attrs.first(), attribute.as_ref(), default_endian)?;
+    for item in items {
+        let read = match item {
+            Item::Field(i, field) => {
+                let binding = binding(*i, field);
+                let args = FieldArgs::parse(&field.attrs)?;
+                let (before, after) = read_padding(&args, &previous, &binding, strict);
+                let read = read_field(&binding, field, &previous, default_endian)?;
+                let read = read_sized(&binding, &args, &previous, read);
+
+                previous.push(binding.clone());
+                let checks = check(&args.asserts, "InvalidData", |condition| {
+                    with_refs(&previous, condition)
+                });
+                let verify = checksum::stored_in(checksums, &binding).map(|coverage| {
+                    let (state, name) = (&coverage.state, binding.to_string());
+                    quote! {
+                        ::endiannezz::internal::verify_checksum(
+                            #name,
+                            #binding,
+                            ::endiannezz::checksum::Checksum::finish(&#state),
+                        )?;
+                    }
+                });
+
+                quote! {
+                    #before
+                    let #binding = #read;
+                    #verify
+                    #checks
+                    #after
+                }
+            }
+            Item::Bitfield(unit) => {
+                previous.extend(unit.bindings().cloned());
+                unit.read(&default_endian.path())
+            }
+        };
+        derived.push(checksum::read(
+            &checksum::covering(checksums, item),
+            read,
+            &item.bindings(),
+        ));
+    }
+
+    let rest = match marked {
+        Some((mark, rest)) => dispatch(&with_refs(&previous, mark), |endian| {
+            read_section(
+                rest,
+                previous.clone(),
+                DefaultEndian::Marked(endian),
+                strict,
+                checksums,
+                constructed,
+            )
+        })?,
+        None => constructed.clone(),
+    };
+
+    Ok(quote!({
+        #(#derived)*
+        #rest
+    }))
+}
+
+/// Items before the byte order mark, and the mark along with items after it
+type Sections<'i, 'a> = (&'i [Item<'a>], Option<(&'a Expr, &'i [Item<'a>])>);
+
+/// Splits items after the last one that is mentioned by the expression of
+/// `#[endian(from = expr)]`, the rest is returned along with the expression
+fn split_at_mark<'i, 'a>(
+    items: &'i [Item<'a>],
+    default_endian: DefaultEndian<'a>,
+) -> Result<Sections<'i, 'a>> {
+    let mark = match default_endian {
+        DefaultEndian::From(mark) => mark,
+        _ => return Ok((items, None)),
+    };
+
+    let mut idents = Vec::new();
+    mark_fields(mark, &mut idents);
+
+    let position = items
+        .iter()
+        .rposition(|item| {
+            item.bindings()
+                .iter()
+                .any(|binding| idents.contains(binding))
+        })
+        .ok_or_else(|| Error::new_spanned(mark, "byte order mark must refer to a field"))?;
 
-                derived.push(write_field(&accessor, &endian));
+    let (items, rest) = items.split_at(position + 1);
+    for item in items {
+        expect_endian_before_mark(item)?;
+    }
+    Ok((items, Some((mark, rest))))
+}
+
+/// Collects variables of `expr` that may name fields, which are paths of a single identifier.
+/// Members, methods and functions that are called by name are not collected
+fn mark_fields(expr: &Expr, idents: &mut Vec<Ident>) {
+    match expr {
+        Expr::Path(path) if path.qself.is_none() => idents.extend(path.path.get_ident().cloned()),
+        Expr::Array(array) => array.elems.iter().for_each(|e| mark_fields(e, idents)),
+        Expr::Tuple(tuple) => tuple.elems.iter().for_each(|e| mark_fields(e, idents)),
+        Expr::Call(call) => {
+            if !matches!(&*call.func, Expr::Path(_)) {
+                mark_fields(&call.func, idents);
             }
+            call.args.iter().for_each(|e| mark_fields(e, idents));
         }
-        Fields::Unnamed(fields) => {
-            for (i, field) in fields.unnamed.iter().enumerate() {
-                let accessor = access_unnamed(i);
-                let attribute = endian::parse(&field.attrs)?;
-                let endian =
-                    endian::choice(field.attrs.first(), attribute.as_ref(), default_endian)?;
+        Expr::MethodCall(call) => {
+            mark_fields(&call.receiver, idents);
+            call.args.iter().for_each(|e| mark_fields(e, idents));
+        }
+        Expr::Binary(binary) => {
+            mark_fields(&binary.left, idents);
+            mark_fields(&binary.right, idents);
+        }
+        Expr::Index(index) => {
+            mark_fields(&index.expr, idents);
+            mark_fields(&index.index, idents);
+        }
+        Expr::Range(range) => {
+            range.from.iter().for_each(|e| mark_fields(e, idents));
+            range.to.iter().for_each(|e| mark_fields(e, idents));
+        }
+        Expr::If(expr_if) => {
+            mark_fields(&expr_if.cond, idents);
+            mark_block(&expr_if.then_branch, idents);
+            if let Some((_, else_branch)) = &expr_if.else_branch {
+                mark_fields(else_branch, idents);
+            }
+        }
+        Expr::Match(expr_match) => {
+            mark_fields(&expr_match.expr, idents);
+            for arm in &expr_match.arms {
+                if let Some((_, guard)) = &arm.guard {
+                    mark_fields(guard, idents);
+                }
+                mark_fields(&arm.body, idents);
+            }
+        }
+        Expr::Struct(expr_struct) => {
+            for field in &expr_struct.fields {
+                mark_fields(&field.expr, idents);
+            }
+            expr_struct.rest.iter().for_each(|e| mark_fields(e, idents));
+        }
+        Expr::Block(block) => mark_block(&block.block, idents),
+        Expr::Unsafe(block) => mark_block(&block.block, idents),
+        Expr::Field(field) => mark_fields(&field.base, idents),
+        Expr::Unary(unary) => mark_fields(&unary.expr, idents),
+        Expr::Reference(reference) => mark_fields(&reference.expr, idents),
+        Expr::Cast(cast) => mark_fields(&cast.expr, idents),
+        Expr::Paren(paren) => mark_fields(&paren.expr, idents),
+        Expr::Group(group) => mark_fields(&group.expr, idents),
+        Expr::Try(expr_try) => mark_fields(&expr_try.expr, idents),
+        Expr::Let(expr_let) => mark_fields(&expr_let.expr, idents),
+        Expr::Closure(closure) => mark_fields(&closure.body, idents),
+        _ => {}
+    }
+}
 
-                derived.push(write_field(&accessor, &endian));
+fn mark_block(block: &Block, idents: &mut Vec<Ident>) {
+    for stmt in &block.stmts {
+        match stmt {
+            Stmt::Local(local) => {
+                if let Some((_, init)) = &local.init {
+                    mark_fields(init, idents);
+                }
             }
+            Stmt::Expr(expr) | Stmt::Semi(expr, _) => mark_fields(expr, idents),
+            Stmt::Item(_) => {}
         }
-        Fields::Unit => {}
     }
+}
 
-    Ok(quote!(#(#derived)*))
+/// Fields up to the byte order mark have no byte order to inherit,
+/// so the ones whose encoding depends on it must declare their own
+fn expect_endian_before_mark(item: &Item) -> Result<()> {
+    let field = match item {
+        Item::Field(_, field) => field,
+        Item::Bitfield(unit) if unit.multibyte() => {
+            return Err(Error::new_spanned(
+                unit.bindings().next(),
+                "bitfield before byte order mark must fit in a single byte",
+            ))
+        }
+        Item::Bitfield(_) => return Ok(()),
+    };
+
+    let args = FieldArgs::parse(&field.attrs)?;
+    let sensitive = if args.skip.is_some() || attr::find(&field.attrs, "endian").is_some() {
+        false
+    } else if args.utf16.is_some() || args.with.is_some() {
+        true
+    } else if args.varint.is_some() || args.cstr.is_some() || args.fixed.is_some() {
+        false
+    } else if let Some(wire) = &args.wire {
+        depends_on_endian(wire)
+    } else {
+        let prefixed = match (&args.len, &args.len_endian) {
+            (Some(len), None) => depends_on_endian(len),
+            _ => false,
+        };
+        prefixed || depends_on_endian(&field.ty)
+    };
+
+    if sensitive {
+        return Err(Error::new_spanned(
+            field,
+            "fields before byte order mark must declare #[endian]",
+        ));
+    }
+    Ok(())
 }
 
-fn write_field(name: &TokenStream, endian: &Ident) -> TokenStream {
-    quote! {
-        #name.write_hacked::<::endiannezz::#endian, _>(&mut w)?;
+/// Whether `ty` contains primitives that take more than one byte,
+/// nested types have their own byte order
+fn depends_on_endian(ty: &Type) -> bool {
+    match ty {
+        Type::Array(array) => depends_on_endian(&array.elem),
+        Type::Tuple(tuple) => tuple.elems.iter().any(depends_on_endian),
+        Type::Group(group) => depends_on_endian(&group.elem),
+        Type::Paren(paren) => depends_on_endian(&paren.elem),
+        _ => match vec_elem(ty).or_else(|| option_elem(ty)) {
+            Some(elem) => depends_on_endian(elem),
+            None => {
+                primitive(ty).is_some_and(|ident| ident != "u8" && ident != "i8" && ident != "bool")
+            }
+        },
     }
 }
 
-pub fn read(fields: &Fields, default_endian: &Ident) -> Result<TokenStream> {
-    let mut derived = Vec::new();
+/// Generates code for each byte order that `mark` can return
+fn dispatch<F>(mark: &TokenStream, mut f: F) -> Result<TokenStream>
+where
+    F: FnMut(&Ident) -> Result<TokenStream>,
+{
+    let (little, big) = (
+        f(&Ident::new("LittleEndian", Span::call_site()))?,
+        f(&Ident::new("BigEndian", Span::call_site()))?,
+    );
 
-    Ok(match fields {
-        Fields::Named(fields) => {
-            for field in &fields.named {
-                let ident = field.ident.as_ref();
-                let attribute = endian::parse(&field.attrs)?;
-                let endian =
-                    endian::choice(field.attrs.first(), attribute.as_ref(), default_endian)?;
+    Ok(quote! {
+        match ::endiannezz::ByteOrderMark::endianness(&#mark)? {
+            ::endiannezz::Endianness::Little => { #little }
+            ::endiannezz::Endianness::Big => { #big }
+        }
+    })
+}
+
+pub fn binding(i: usize, field: &Field) -> Ident {
+    match &field.ident {
+        Some(ident) => ident.clone(),
+        None => generate_pattern(i),
+    }
+}
+
+/// Makes previously read fields available to an expression by reference,
+/// the same way as they are bound in derived `write`
+fn with_refs(previous: &[Ident], expr: &impl quote::ToTokens) -> TokenStream {
+    quote! {{
+        #(
+            #[allow(unused_variables)]
+            let #previous = &#previous;
+        )*
+        #expr
+    }}
+}
 
-                let read = read_field(&field.ty, &endian);
+/// Skipped fields are not encoded, so they can't have byte order
+fn expect_not_endian(field: &Field) -> Result<()> {
+    match attr::find(&field.attrs, "endian") {
+        Some(attribute) => Err(Error::new_spanned(
+            attribute,
+            "endian cannot be used with skipped field",
+        )),
+        None => Ok(()),
+    }
+}
 
-                derived.push(quote!(#ident: #read));
+fn field_endian(field: &Field, default_endian: DefaultEndian) -> Result<Dispatch> {
+    let attribute = endian::parse(&field.attrs)?;
+
+    Ok(match attribute {
+        Some(attribute) => {
+            //nested types are forced to the byte order even if it matches the default
+            if let (DefaultEndian::Declared(declared), false) =
+                (default_endian, nests_io(&field.ty))
+            {
+                endian::choice(
+                    attr::find(&field.attrs, "endian"),
+                    Some(&attribute),
+                    declared,
+                )?;
+            }
+            Dispatch {
+                path: quote!(::endiannezz::#attribute),
+                forced: true,
+                args: None,
             }
-            quote!({ #(#derived),* })
         }
-        Fields::Unnamed(fields) => {
-            for field in &fields.unnamed {
-                let attribute = endian::parse(&field.attrs)?;
-                let endian =
-                    endian::choice(field.attrs.first(), attribute.as_ref(), default_endian)?;
+        None => Dispatch {
+            path: default_endian.path(),
+            forced: default_endian.forced(),
+            args: None,
+        },
+    })
+}
 
-                derived.push(read_field(&field.ty, &endian));
+/// Returns endian of the length prefix, which is the field endian unless it's overridden
+fn len_endian(args: &FieldArgs, endian: &Dispatch) -> TokenStream {
+    match &args.len_endian {
+        Some(len_endian) => quote!(::endiannezz::#len_endian),
+        None => endian.path.clone(),
+    }
+}
+
+/// Returns `T` if `ty` is written as `Vec<T>`
+fn vec_elem(ty: &Type) -> Option<&Type> {
+    wrapped_type(ty, "Vec")
+}
+
+/// Returns `T` if `ty` is written as `Option<T>`
+fn option_elem(ty: &Type) -> Option<&Type> {
+    wrapped_type(ty, "Option")
+}
+
+fn wrapped_type<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let segment = match ty {
+        Type::Path(path) if path.qself.is_none() => path.path.segments.last()?,
+        Type::Group(group) => return wrapped_type(&group.elem, wrapper),
+        _ => return None,
+    };
+
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if segment.ident == wrapper && args.args.len() == 1 => {
+            match args.args.first()? {
+                GenericArgument::Type(elem) => Some(elem),
+                _ => None,
             }
-            quote!(( #(#derived),* ))
         }
-        Fields::Unit => quote!(),
+        _ => None,
+    }
+}
+
+/// Whether `ty` contains types other than primitives, which get byte order of the field
+fn nests_io(ty: &Type) -> bool {
+    match ty {
+        Type::Array(array) => nests_io(&array.elem),
+        Type::Tuple(tuple) => tuple.elems.iter().any(nests_io),
+        Type::Group(group) => nests_io(&group.elem),
+        Type::Paren(paren) => nests_io(&paren.elem),
+        _ if is_string(ty) => false,
+        _ => match vec_elem(ty).or_else(|| option_elem(ty)) {
+            Some(elem) => nests_io(elem),
+            None => !is_primitive(ty),
+        },
+    }
+}
+
+fn is_primitive(ty: &Type) -> bool {
+    primitive(ty).is_some()
+}
+
+/// Returns the name of primitive type if `ty` is one
+fn primitive(ty: &Type) -> Option<&Ident> {
+    const PRIMITIVES: &[&str] = &[
+        "bool", "char", "u8", "u16", "u32", "u64", "u128", "i8", "i16", "i32", "i64", "i128",
+        "f32", "f64",
+    ];
+
+    match ty {
+        Type::Path(path) if path.qself.is_none() => path
+            .path
+            .get_ident()
+            .filter(|ident| PRIMITIVES.iter().any(|primitive| ident == primitive)),
+        Type::Group(group) => primitive(&group.elem),
+        _ => None,
+    }
+}
+
+fn is_string(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "String" && segment.arguments.is_empty()),
+        Type::Group(group) => is_string(&group.elem),
+        _ => false,
+    }
+}
+
+fn collection_elem<'a>(field: &'a Field, attr_name: &str, expected: &str) -> Result<&'a Type> {
+    vec_elem(&field.ty).ok_or_else(|| {
+        Error::new_spanned(
+            &field.ty,
+            format!("{} can be used only with {} fields", attr_name, expected),
+        )
+    })
+}
+
+fn expect_string(field: &Field, attr_name: &str) -> Result<()> {
+    if is_string(&field.ty) {
+        Ok(())
+    } else {
+        Err(Error::new_spanned(
+            &field.ty,
+            format!("{} can be used only with String fields", attr_name),
+        ))
+    }
+}
+
+pub fn write_field(
+    name: &Ident,
+    field: &Field,
+    default_endian: DefaultEndian,
+) -> Result<TokenStream> {
+    let w = writer();
+    let args = FieldArgs::parse(&field.attrs)?;
+    if args.skip.is_some() {
+        expect_not_endian(field)?;
+        return Ok(TokenStream::new());
+    }
+    let mut endian = field_endian(field, default_endian)?;
+    endian.args = args.args.as_ref().map(context);
+
+    Ok(if let (Some(len), Some(_)) = (&args.len, &args.utf16) {
+        expect_string(field, "utf16")?;
+        let len_endian = len_endian(&args, &endian);
+        quote! {
+            ::endiannezz::ext::EndianWriter::write_len::<#len_endian, #len>(
+                &mut #w,
+                #name.encode_utf16().count(),
+            )?;
+            ::endiannezz::strings::write_utf16::<#endian, _>(#name, &mut #w)?;
+        }
+    } else if let (Some(len), true) = (&args.len, is_string(&field.ty)) {
+        let len_endian = len_endian(&args, &endian);
+        quote! {
+            ::endiannezz::strings::write_prefixed::<#len_endian, #len, _>(
+                #name,
+                &mut #w,
+            )?;
+        }
+    } else if let Some(len) = &args.len {
+        let elem = collection_elem(field, "len", "Vec<T> or String")?;
+        let len_endian = len_endian(&args, &endian);
+        let items = write_items(name, elem, &endian);
+        quote! {
+            ::endiannezz::ext::EndianWriter::write_len::<#len_endian, #len>(
+                &mut #w,
+                #name.len(),
+            )?;
+            #items
+        }
+    } else if let Some(count) = &args.count {
+        let elem = collection_elem(field, "count", "Vec<T>")?;
+        let items = write_items(name, elem, &endian);
+        let message = format!("count does not match length of `{}`", name);
+        quote! {
+            if ::endiannezz::internal::Count::count(#count)? != #name.len() {
+                return Err(::std::io::Error::new(::std::io::ErrorKind::InvalidInput, #message));
+            }
+            #items
+        }
+    } else if let (Some(_), Some(_)) = (&args.cstr, &args.utf16) {
+        expect_string(field, "utf16")?;
+        quote! {
+            ::endiannezz::strings::write_utf16_nul_terminated::<#endian, _>(
+                #name,
+                &mut #w,
+            )?;
+        }
+    } else if args.cstr.is_some() {
+        expect_string(field, "cstr")?;
+        quote! {
+            ::endiannezz::strings::write_nul_terminated(#name, &mut #w)?;
+        }
+    } else if let Some(fixed) = &args.fixed {
+        expect_string(field, "fixed")?;
+        let pad = pad(&args);
+        quote! {
+            ::endiannezz::strings::write_padded(#name, #fixed, #pad, &mut #w)?;
+        }
+    } else if let Some(encoding) = &args.varint {
+        let write = match encoding.to_string().as_str() {
+            "zigzag" => quote!(::endiannezz::varint::VarintWriter::write_zigzag(&mut #w, *#name)),
+            "vlq" => quote!(::endiannezz::varint::VarintWriter::write_vlq(&mut #w, *#name)),
+            _ => quote!(::endiannezz::varint::Leb128::write_leb128(*#name, &mut #w)),
+        };
+        quote!(#write?;)
+    } else if let Some(sentinel) = &args.sentinel {
+        let elem = option_elem(&field.ty).ok_or_else(|| {
+            Error::new_spanned(&field.ty, "sentinel can be used only with Option<T> fields")
+        })?;
+        let element = element();
+        let item = write_type(&quote!(#element), elem, &endian);
+        let message = format!("`{}` holds value reserved for None", name);
+        quote! {
+            match #name {
+                Some(#element) if *#element == #sentinel => {
+                    return Err(::std::io::Error::new(
+                        ::std::io::ErrorKind::InvalidInput,
+                        #message,
+                    ));
+                }
+                Some(#element) => {
+                    #item
+                }
+                None => {
+                    let #element: #elem = #sentinel;
+                    #item
+                }
+            }
+        }
+    } else if let Some(with) = &args.with {
+        quote! {
+            #with::write::<#endian, _>(#name, &mut #w)?;
+        }
+    } else if let (Some(map), Some(wire_ty)) = (&args.map, &args.wire) {
+        let wire = Ident::new("wire", Span::mixed_site());
+        let item = write_type(&quote!(#wire), wire_ty, &endian);
+        let (write, message) = (&map.write, format!("cannot convert `{}`", name));
+        let convert = match map.fallible {
+            true => quote! {
+                ::endiannezz::internal::try_map(
+                    #name,
+                    #write,
+                    ::std::io::ErrorKind::InvalidInput,
+                    #message,
+                )?
+            },
+            false => quote!(::endiannezz::internal::map(#name, #write)),
+        };
+        quote! {
+            let #wire: #wire_ty = #convert;
+            #item
+        }
+    } else if let Some(condition) = &args.condition {
+        let elem = option_elem(&field.ty).ok_or_else(|| {
+            Error::new_spanned(&field.ty, "if can be used only with Option<T> fields")
+        })?;
+        let element = element();
+        let item = write_type(&quote!(#element), elem, &endian);
+        let message = format!("presence of `{}` does not match its condition", name);
+        quote! {
+            match (#condition, #name) {
+                (true, Some(#element)) => {
+                    #item
+                }
+                (false, None) => {}
+                _ => {
+                    return Err(::std::io::Error::new(
+                        ::std::io::ErrorKind::InvalidInput,
+                        #message,
+                    ));
+                }
+            }
+        }
+    } else {
+        write_type(&quote!(#name), &field.ty, &endian)
     })
 }
 
-fn read_field(ty: &Type, endian: &Ident) -> TokenStream {
+/// Generates checks of `#[io(assert = ...)]` which fail with error of the given kind
+fn check<F>(asserts: &[Assert], kind: &str, mut condition: F) -> TokenStream
+where
+    F: FnMut(&Expr) -> TokenStream,
+{
+    let kind = Ident::new(kind, Span::call_site());
+    let checks = asserts.iter().map(|assert| {
+        let (condition, message) = (condition(&assert.condition), assert.message());
+        quote! {
+            if !(#condition) {
+                return Err(::std::io::Error::new(::std::io::ErrorKind::#kind, #message));
+            }
+        }
+    });
+    quote!(#(#checks)*)
+}
+
+/// Counts bytes of `#[io(size = expr)]` field, they must match the size exactly
+fn write_sized(name: &Ident, args: &FieldArgs, write: TokenStream) -> TokenStream {
+    let size = match &args.size {
+        Some(size) => size,
+        None => return write,
+    };
+
+    let w = writer();
+    let name = name.to_string();
+    quote! {{
+        let mut #w = ::endiannezz::internal::Position::new(&mut #w);
+        #write
+        ::endiannezz::internal::check_size(&#w, ::endiannezz::internal::Count::count(#size)?, #name)?;
+    }}
+}
+
+/// Limits the reader to `#[io(size = expr)]` bytes, the rest of them is handled after the field
+fn read_sized(
+    name: &Ident,
+    args: &FieldArgs,
+    previous: &[Ident],
+    read: TokenStream,
+) -> TokenStream {
+    let size = match &args.size {
+        Some(size) => with_refs(previous, size),
+        None => return read,
+    };
+
+    let r = reader();
+    let name = name.to_string();
+    let exact = matches!(&args.trailing, Some(trailing) if trailing == "error");
+    let limit = Ident::new("limit", Span::mixed_site());
+    quote! {{
+        let #limit = ::endiannezz::internal::Count::count(#size)?;
+        let mut #r = ::std::io::Read::take(&mut #r, #limit as u64);
+        let value = #read;
+        ::endiannezz::internal::skip_trailing(#r, #exact, #name)?;
+        value
+    }}
+}
+
+/// Generates zeros which are written before and after the field
+fn write_padding(args: &FieldArgs) -> (TokenStream, TokenStream) {
+    let w = writer();
+    let pad = |n: TokenStream| {
+        quote! {{
+            let n = #n;
+            ::endiannezz::internal::write_padding(&mut #w, n)?;
+        }}
+    };
+
+    let pad_before = args.pad_before.as_ref().map(|n| pad(quote!(#n)));
+    let align = args
+        .align
+        .as_ref()
+        .map(|align| pad(quote!(::endiannezz::internal::align_padding(&#w, #align))));
+    let pad_after = args.pad_after.as_ref().map(|n| pad(quote!(#n)));
+
+    (quote!(#pad_before #align), quote!(#pad_after))
+}
+
+/// Generates skipping of padding before and after the field, it's checked in strict mode
+fn read_padding(
+    args: &FieldArgs,
+    previous: &[Ident],
+    binding: &Ident,
+    strict: bool,
+) -> (TokenStream, TokenStream) {
+    let r = reader();
+    let pad = |n: TokenStream| {
+        quote! {{
+            let n = #n;
+            ::endiannezz::internal::read_padding(&mut #r, n, #strict)?;
+        }}
+    };
+
+    let pad_before = args
+        .pad_before
+        .as_ref()
+        .map(|n| pad(with_refs(previous, n)));
+    let align = args.align.as_ref().map(|align| {
+        let align = with_refs(previous, align);
+        pad(quote!(::endiannezz::internal::align_padding(&#r, #align)))
+    });
+    let pad_after = args.pad_after.as_ref().map(|n| {
+        let mut previous = previous.to_vec();
+        previous.push(binding.clone());
+        pad(with_refs(&previous, n))
+    });
+
+    (quote!(#pad_before #align), quote!(#pad_after))
+}
+
+/// Builds context tuple of `#[io(args(...))]`
+fn context(args: &Punctuated<Expr, Token![,]>) -> TokenStream {
+    let args = args.iter();
+    quote!((#(#args,)*))
+}
+
+fn pad(args: &FieldArgs) -> TokenStream {
+    match &args.pad {
+        Some(pad) => quote!(#pad),
+        None => quote!(0),
+    }
+}
+
+fn surrogates(args: &FieldArgs) -> TokenStream {
+    match &args.lossy {
+        Some(_) => quote!(::endiannezz::strings::Surrogates::Replace),
+        None => quote!(::endiannezz::strings::Surrogates::Reject),
+    }
+}
+
+fn write_items(name: &Ident, elem: &Type, endian: &Dispatch) -> TokenStream {
+    let element = element();
+    let item = write_type(&quote!(#element), elem, endian);
     quote! {
-        <#ty>::read_hacked::<::endiannezz::#endian, _>(&mut r)?
+        for #element in #name.iter() {
+            #item
+        }
+    }
+}
+
+fn write_type(name: &TokenStream, ty: &Type, endian: &Dispatch) -> TokenStream {
+    let w = writer();
+    match ty {
+        Type::Array(array) => {
+            let element = element();
+            let item = write_type(&quote!(#element), &array.elem, endian);
+            quote! {
+                for #element in #name.iter() {
+                    #item
+                }
+            }
+        }
+        Type::Tuple(tuple) => {
+            let items = tuple.elems.iter().enumerate().map(|(i, elem)| {
+                let i = Literal::usize_unsuffixed(i);
+                write_type(&quote!(#name.#i), elem, endian)
+            });
+            quote!(#(#items)*)
+        }
+        //presence flag is written as `bool`, followed by the value if it exists
+        Type::Path(_) if option_elem(ty).is_some() => {
+            let element = element();
+            let item = write_type(&quote!(#element), option_elem(ty).unwrap(), endian);
+            quote! {
+                match &#name {
+                    Some(#element) => {
+                        ::endiannezz::Io::write(&true, &mut #w)?;
+                        #item
+                    }
+                    None => ::endiannezz::Io::write(&false, &mut #w)?,
+                }
+            }
+        }
+        Type::Group(group) => write_type(name, &group.elem, endian),
+        Type::Paren(paren) => write_type(name, &paren.elem, endian),
+        _ => match &endian.args {
+            Some(args) if endian.forced => quote! {
+                #name.write_with_endian::<#endian, _>(&mut #w, #args)?;
+            },
+            Some(args) => quote! {
+                #name.write_with(&mut #w, #args)?;
+            },
+            None if endian.forced => quote! {
+                (&&::endiannezz::internal::Forced::<#ty>::new())
+                    .write_forced::<#endian, _>(&#name, &mut #w)?;
+            },
+            None => quote! {
+                #name.write_hacked::<#endian, _>(&mut #w)?;
+            },
+        },
+    }
+}
+
+fn read_field(
+    name: &Ident,
+    field: &Field,
+    previous: &[Ident],
+    default_endian: DefaultEndian,
+) -> Result<TokenStream> {
+    let (r, n) = (reader(), Ident::new("len", Span::mixed_site()));
+    let args = FieldArgs::parse(&field.attrs)?;
+    if let Some(default) = &args.skip {
+        expect_not_endian(field)?;
+        return Ok(with_refs(previous, default));
+    }
+    let mut endian = field_endian(field, default_endian)?;
+    endian.args = args
+        .args
+        .as_ref()
+        .map(|args| with_refs(previous, &context(args)));
+
+    Ok(if let (Some(len), Some(_)) = (&args.len, &args.utf16) {
+        expect_string(field, "utf16")?;
+        let len_endian = len_endian(&args, &endian);
+        let surrogates = surrogates(&args);
+        quote! {{
+            let #n = ::endiannezz::ext::EndianReader::read_len::<
+                #len_endian,
+                #len,
+            >(&mut #r)?;
+            ::endiannezz::strings::read_utf16::<#endian, _>(&mut #r, #n, #surrogates)?
+        }}
+    } else if let (Some(len), true) = (&args.len, is_string(&field.ty)) {
+        let len_endian = len_endian(&args, &endian);
+        quote! {
+            ::endiannezz::strings::read_prefixed::<#len_endian, #len, _>(&mut #r)?
+        }
+    } else if let Some(len) = &args.len {
+        let elem = collection_elem(field, "len", "Vec<T> or String")?;
+        let len_endian = len_endian(&args, &endian);
+        let item = read_value(elem, &endian);
+        quote! {{
+            let #n = ::endiannezz::ext::EndianReader::read_len::<
+                #len_endian,
+                #len,
+            >(&mut #r)?;
+            ::endiannezz::internal::read_vec(#n, || #item)?
+        }}
+    } else if let Some(count) = &args.count {
+        let elem = collection_elem(field, "count", "Vec<T>")?;
+        let count = with_refs(previous, count);
+        let item = read_value(elem, &endian);
+        quote! {{
+            let #n = ::endiannezz::internal::Count::count(#count)?;
+            ::endiannezz::internal::read_vec(#n, || #item)?
+        }}
+    } else if let (Some(_), Some(_)) = (&args.cstr, &args.utf16) {
+        expect_string(field, "utf16")?;
+        let surrogates = surrogates(&args);
+        quote! {
+            ::endiannezz::strings::read_utf16_nul_terminated::<#endian, _>(
+                &mut #r,
+                #surrogates,
+            )?
+        }
+    } else if args.cstr.is_some() {
+        expect_string(field, "cstr")?;
+        quote! {
+            ::endiannezz::strings::read_nul_terminated(&mut #r)?
+        }
+    } else if let Some(fixed) = &args.fixed {
+        expect_string(field, "fixed")?;
+        let pad = pad(&args);
+        quote! {
+            ::endiannezz::strings::read_padded(&mut #r, #fixed, #pad)?
+        }
+    } else if let Some(encoding) = &args.varint {
+        let ty = &field.ty;
+        let read = match encoding.to_string().as_str() {
+            "zigzag" => quote!(::endiannezz::varint::VarintReader::read_zigzag::<#ty>(&mut #r)),
+            "vlq" => quote!(::endiannezz::varint::VarintReader::read_vlq::<#ty>(&mut #r)),
+            _ => quote!(<#ty as ::endiannezz::varint::Leb128>::read_leb128(&mut #r)),
+        };
+        quote!(#read?)
+    } else if let Some(sentinel) = &args.sentinel {
+        let elem = option_elem(&field.ty).ok_or_else(|| {
+            Error::new_spanned(&field.ty, "sentinel can be used only with Option<T> fields")
+        })?;
+        let (element, item) = (element(), read_type(elem, &endian));
+        quote! {{
+            let #element = #item;
+            if #element == #sentinel {
+                None
+            } else {
+                Some(#element)
+            }
+        }}
+    } else if let Some(with) = &args.with {
+        quote! {
+            #with::read::<#endian, _>(&mut #r)?
+        }
+    } else if let (Some(map), Some(wire_ty)) = (&args.map, &args.wire) {
+        let item = read_type(wire_ty, &endian);
+        let read = with_refs(previous, &map.read);
+        let message = format!("cannot convert `{}`", name);
+        match map.fallible {
+            true => quote! {
+                ::endiannezz::internal::try_map::<#wire_ty, _, _, _>(
+                    #item,
+                    #read,
+                    ::std::io::ErrorKind::InvalidData,
+                    #message,
+                )?
+            },
+            false => quote!(::endiannezz::internal::map::<#wire_ty, _, _>(#item, #read)),
+        }
+    } else if let Some(condition) = &args.condition {
+        let elem = option_elem(&field.ty).ok_or_else(|| {
+            Error::new_spanned(&field.ty, "if can be used only with Option<T> fields")
+        })?;
+        let condition = with_refs(previous, condition);
+        let item = read_type(elem, &endian);
+        quote! {
+            if #condition {
+                Some(#item)
+            } else {
+                None
+            }
+        }
+    } else {
+        read_type(&field.ty, &endian)
+    })
+}
+
+fn read_type(ty: &Type, endian: &Dispatch) -> TokenStream {
+    match ty {
+        Type::Tuple(tuple) => {
+            let items = tuple.elems.iter().map(|elem| read_type(elem, endian));
+            quote!(( #(#items,)* ))
+        }
+        Type::Path(_) if option_elem(ty).is_some() => {
+            let r = reader();
+            let item = read_type(option_elem(ty).unwrap(), endian);
+            quote! {
+                if <bool as ::endiannezz::Io>::read(&mut #r)? {
+                    Some(#item)
+                } else {
+                    None
+                }
+            }
+        }
+        Type::Group(group) => read_type(&group.elem, endian),
+        Type::Paren(paren) => read_type(&paren.elem, endian),
+        _ => {
+            let value = read_value(ty, endian);
+            quote!(#value?)
+        }
+    }
+}
+
+fn read_value(ty: &Type, endian: &Dispatch) -> TokenStream {
+    let r = reader();
+    match ty {
+        //every element is dispatched on its own, so `[u16; N]` gets the field endian too
+        Type::Array(array) => {
+            let item = read_value(&array.elem, endian);
+            quote! {
+                ::endiannezz::internal::read_array(|| #item)
+            }
+        }
+        Type::Tuple(_) => {
+            let tuple = read_type(ty, endian);
+            quote!(Ok(#tuple))
+        }
+        Type::Path(_) if option_elem(ty).is_some() => {
+            let option = read_type(ty, endian);
+            quote!(Ok(#option))
+        }
+        Type::Group(group) => read_value(&group.elem, endian),
+        Type::Paren(paren) => read_value(&paren.elem, endian),
+        _ => match &endian.args {
+            Some(args) if endian.forced => quote! {
+                <#ty as ::endiannezz::IoWith<_>>::read_with_endian::<#endian, _>(&mut #r, #args)
+            },
+            Some(args) => quote! {
+                <#ty as ::endiannezz::IoWith<_>>::read_with(&mut #r, #args)
+            },
+            None if endian.forced => quote! {
+                (&&::endiannezz::internal::Forced::<#ty>::new()).read_forced::<#endian, _>(&mut #r)
+            },
+            None => quote! {
+                <#ty>::read_hacked::<#endian, _>(&mut #r)
+            },
+        },
     }
 }
 
 pub fn generate_pattern(i: usize) -> Ident {
-    format_ident!("variant_{}", i)
+    format_ident!("field_{}", i)
 }
 
 pub fn make_patterns(fields: &Fields) -> TokenStream {
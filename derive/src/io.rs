@@ -1,37 +1,33 @@
-use crate::{attr, fields};
-use proc_macro2::{Ident, Literal, TokenStream};
+use crate::attr::{self, endian::TypeEndian, io::ContainerArgs};
+use crate::fields::{self, DefaultEndian};
+use crate::length;
+use proc_macro2::{Ident, TokenStream};
 use quote::quote;
-use syn::{Data, DeriveInput, Error, Fields, Result};
+use syn::{Data, DataEnum, DeriveInput, Error, Fields, Lit, Result};
 
 pub fn derive(input: DeriveInput) -> Result<TokenStream> {
     let name = &input.ident;
 
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
-    let default = attr::endian::parse(&input.attrs)?
+    let default = attr::endian::parse_type(&input.attrs)?
         .ok_or_else(|| Error::new_spanned(&input, "please specify default endian"))?;
 
+    let container = ContainerArgs::parse(&input.attrs)?;
+
+    let (w, r, e) = (fields::writer(), fields::reader(), fields::endian_param());
+
     let imports = quote! {
         #[allow(unused_imports)]
-        use ::endiannezz::{Endian, internal::{HackedIo, HackedPrimitive}};
+        use ::endiannezz::{
+            Endian,
+            IoWith,
+            internal::{ForcedEndian, ForcedIo, ForcedPrimitive, HackedIo, HackedPrimitive},
+        };
     };
 
-    let (write, read) = match &input.data {
-        Data::Struct(data) => {
-            let write = fields::write(
-                &data.fields,
-                |ident| quote!(self.#ident),
-                |i| {
-                    let i = Literal::usize_unsuffixed(i);
-                    quote!(self.#i)
-                },
-                &default,
-            )?;
-            let read = fields::read(&data.fields, &default)?;
-
-            (write, quote!(Self #read))
-        }
-        Data::Enum(data) => {
+    let repr_ty = match &input.data {
+        Data::Enum(_) => {
             let repr_attr = attr::find(&input.attrs, "repr")
                 .ok_or_else(|| Error::new_spanned(&input, "Enums must declare #[repr]"))?;
             let repr_ty = repr_attr.parse_args::<Ident>()?;
@@ -39,93 +35,423 @@ pub fn derive(input: DeriveInput) -> Result<TokenStream> {
             if !repr_ty.to_string().starts_with(|c| matches!(c, 'u' | 'i')) {
                 return Err(Error::new_spanned(&repr_attr, "Unsupported repr type"));
             }
+            Some(repr_ty)
+        }
+        _ => None,
+    };
 
-            let capacity = data.variants.len();
-
-            let (mut write_vars, mut read_vars) =
-                (Vec::with_capacity(capacity), Vec::with_capacity(capacity));
-
-            let (repr_write, repr_read) = (
-                quote!(::endiannezz::#default::write::<#repr_ty, _>),
-                quote!(::endiannezz::#default::read::<#repr_ty, _>),
-            );
-
-            let cloneable = data
-                .variants
-                .iter()
-                .all(|v| matches!(&v.fields, Fields::Unit));
-
-            for variant in &data.variants {
-                let variant_name = &variant.ident;
-
-                let (_, discriminant) = variant.discriminant.as_ref().ok_or_else(|| {
-                    Error::new_spanned(
-                        variant,
-                        "All enum variants must have explicit discriminants",
-                    )
-                })?;
-
-                let fields_patterns = fields::make_patterns(&variant.fields);
-                let fields_write = fields::write(
-                    &variant.fields,
-                    |ident| quote!(#ident),
-                    |i| {
-                        let ident = fields::generate_pattern(i);
-                        quote!(#ident)
-                    },
-                    &default,
-                )?;
-                let fields_read = fields::read(&variant.fields, &default)?;
-
-                write_vars.push(quote!(Self::#variant_name #fields_patterns => {
-                    #repr_write(#discriminant, &mut w)?;
-                    #fields_write
-                }));
-                read_vars.push(quote!(#discriminant => Self::#variant_name #fields_read));
-            }
+    if let (Data::Enum(_), Some(assert)) = (&input.data, container.asserts.first()) {
+        return Err(Error::new_spanned(
+            &assert.condition,
+            "assert on the type can be used only in structures",
+        ));
+    }
+
+    //`write_endian` and `read_endian` are the same code with the default endian replaced
+    let fields_body = |default_endian, seek| match (&input.data, &repr_ty) {
+        (Data::Struct(data), _) => {
+            let patterns = fields::make_patterns(&data.fields);
+            let write = fields::write(&data.fields, default_endian, &container, seek)?;
+            let read = fields::read(&data.fields, quote!(Self), default_endian, &container)?;
 
-            let write = if cloneable {
+            Ok((
                 quote! {
-                    #repr_write(*self as #repr_ty, &mut w)?;
+                    let Self #patterns = self;
+                    #write
+                },
+                read,
+            ))
+        }
+        (Data::Enum(data), Some(repr_ty)) => {
+            enum_body(data, repr_ty, default_endian, &container, seek)
+        }
+        _ => Err(Error::new_spanned(
+            &input,
+            "Io can be derived only for structures and enums (in nightly version)",
+        )),
+    };
+
+    //alignment is relative to the start of the type, so the position is counted from here
+    let aligned = match &input.data {
+        Data::Struct(data) => fields::aligned(&data.fields)?,
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .map(|variant| fields::aligned(&variant.fields))
+            .collect::<Result<Vec<_>>>()?
+            .contains(&true),
+        _ => false,
+    };
+    let (write_position, read_position) = if aligned {
+        (
+            quote!(let mut #w = ::endiannezz::internal::Position::new(&mut #w);),
+            quote!(let mut #r = ::endiannezz::internal::Position::new(&mut #r);),
+        )
+    } else {
+        (TokenStream::new(), TokenStream::new())
+    };
+
+    let body = |default_endian, seek| -> Result<_> {
+        let (write, read) = fields_body(default_endian, seek)?;
+        let (write_magic, read_magic) = magic(&container, default_endian)?;
+        Ok((
+            quote! {
+                #write_position
+                #write_magic
+                #write
+            },
+            quote! {{
+                #read_position
+                #read_magic
+                #read
+            }},
+        ))
+    };
+
+    let complete = |default_endian| -> Result<_> {
+        let (write, read) = body(default_endian, false)?;
+        Ok((
+            quote! {
+                #imports
+                #write
+                Ok(())
+            },
+            quote! {
+                #imports
+                Ok(#read)
+            },
+        ))
+    };
+
+    //runtime types have nothing to fall back to, so they implement only `IoEndian`
+    let default_endian = match &default {
+        TypeEndian::Declared(default) => Some(DefaultEndian::Declared(default)),
+        TypeEndian::From(mark) => match &input.data {
+            Data::Struct(_) => Some(DefaultEndian::From(mark)),
+            _ => {
+                return Err(Error::new_spanned(
+                    mark,
+                    "byte order mark can be used only in structures",
+                ))
+            }
+        },
+        TypeEndian::Runtime => None,
+    };
+
+    let bit_field = match (&input.data, &repr_ty) {
+        (Data::Enum(data), Some(repr_ty)) => enum_bit_field(&input, data, repr_ty)?,
+        _ => TokenStream::new(),
+    };
+
+    //lengths are patched in place when the writer can seek, derived `write` buffers instead
+    let measured = match &input.data {
+        Data::Struct(data) => length::measured(&data.fields)?,
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .map(|variant| length::measured(&variant.fields))
+            .collect::<Result<Vec<_>>>()?
+            .contains(&true),
+        _ => false,
+    };
+    let seek_impl = default_endian
+        .filter(|_| measured)
+        .map(|default_endian| -> Result<_> {
+            let (write_seek, _) = body(default_endian, true)?;
+            Ok(quote! {
+                #[automatically_derived]
+                impl #impl_generics ::endiannezz::IoSeek for #name #ty_generics #where_clause {
+                    fn write_seek<W: ::std::io::Write + ::std::io::Seek>(
+                        &self,
+                        mut #w: W,
+                    ) -> ::std::io::Result<()> {
+                        #imports
+                        #write_seek
+                        Ok(())
+                    }
                 }
-            } else {
+            })
+        })
+        .transpose()?;
+
+    //fields depend on the context, so the type can't be read or written without it
+    if let Some(context) = &container.context {
+        let (write, read) = match default_endian {
+            Some(default_endian) => complete(default_endian)?,
+            None => {
+                return Err(Error::new_spanned(
+                    attr::find(&input.attrs, "endian"),
+                    "context cannot be used with runtime endian",
+                ))
+            }
+        };
+
+        let ctx = fields::context_param();
+        let names = context.iter().map(|param| &param.name).collect::<Vec<_>>();
+        let types = context.iter().map(|param| &param.ty);
+        let ty = quote!((#(#types,)*));
+        let bind = quote! {
+            #[allow(unused_variables)]
+            let (#(#names,)*) = #ctx;
+        };
+
+        //the byte order mark decides on its own, so the default methods are kept
+        let endian_methods = match &default {
+            TypeEndian::From(_) => TokenStream::new(),
+            _ => {
+                let (write_endian, read_endian) = body(DefaultEndian::Generic, false)?;
                 quote! {
-                    match self {
-                        #(#write_vars),*
+                    fn write_with_endian<#e: ::endiannezz::Endian, W: ::std::io::Write>(
+                        &self,
+                        mut #w: W,
+                        #ctx: #ty,
+                    ) -> ::std::io::Result<()> {
+                        #bind
+                        #imports
+                        #write_endian
+                        Ok(())
+                    }
+
+                    fn read_with_endian<#e: ::endiannezz::Endian, R: ::std::io::Read>(
+                        mut #r: R,
+                        #ctx: #ty,
+                    ) -> ::std::io::Result<Self> {
+                        #bind
+                        #imports
+                        Ok(#read_endian)
                     }
                 }
-            };
+            }
+        };
 
-            let read = quote! {{
-                match #repr_read(&mut r)? {
-                    #(#read_vars,)*
-                    _ => Err(::std::io::Error::from(::std::io::ErrorKind::InvalidData))?,
+        return Ok(quote! {
+            #[automatically_derived]
+            impl #impl_generics ::endiannezz::IoWith<#ty> for #name #ty_generics #where_clause {
+                fn write_with<W: ::std::io::Write>(
+                    &self,
+                    mut #w: W,
+                    #ctx: #ty,
+                ) -> ::std::io::Result<()> {
+                    #bind
+                    #write
                 }
-            }};
 
-            (write, read)
-        }
+                fn read_with<R: ::std::io::Read>(
+                    mut #r: R,
+                    #ctx: #ty,
+                ) -> ::std::io::Result<Self> {
+                    #bind
+                    #read
+                }
+
+                #endian_methods
+            }
+
+            #bit_field
+        });
+    }
+
+    //the byte order mark decides on its own, so the type can't be written in another order
+    let endian_impl = match &default {
+        TypeEndian::From(_) => TokenStream::new(),
         _ => {
-            return Err(Error::new_spanned(
-                input,
-                "Io can be derived only for structures and enums (in nightly version)",
-            ));
+            let (write_endian, read_endian) = body(DefaultEndian::Generic, false)?;
+            quote! {
+                #[automatically_derived]
+                impl #impl_generics ::endiannezz::IoEndian for #name #ty_generics #where_clause {
+                    fn write_endian<#e: ::endiannezz::Endian, W: ::std::io::Write>(
+                        &self,
+                        mut #w: W,
+                    ) -> ::std::io::Result<()> {
+                        #imports
+                        #write_endian
+                        Ok(())
+                    }
+
+                    fn read_endian<#e: ::endiannezz::Endian, R: ::std::io::Read>(
+                        mut #r: R,
+                    ) -> ::std::io::Result<Self> {
+                        #imports
+                        Ok(#read_endian)
+                    }
+                }
+            }
+        }
+    };
+
+    let io_impl = default_endian
+        .map(|default_endian| -> Result<_> {
+            let (write, read) = complete(default_endian)?;
+            Ok(quote! {
+                #[automatically_derived]
+                impl #impl_generics ::endiannezz::Io for #name #ty_generics #where_clause {
+                    fn write<W: ::std::io::Write>(&self, mut #w: W) -> ::std::io::Result<()> {
+                        #write
+                    }
+
+                    fn read<R: ::std::io::Read>(mut #r: R) -> ::std::io::Result<Self> {
+                        #read
+                    }
+                }
+            })
+        })
+        .transpose()?;
+
+    Ok(quote! {
+        #io_impl
+        #endian_impl
+        #seek_impl
+        #bit_field
+    })
+}
+
+fn enum_body(
+    data: &DataEnum,
+    repr_ty: &Ident,
+    default_endian: DefaultEndian,
+    container: &ContainerArgs,
+    seek: bool,
+) -> Result<(TokenStream, TokenStream)> {
+    let (w, r) = (fields::writer(), fields::reader());
+
+    let capacity = data.variants.len();
+
+    let (mut write_vars, mut read_vars) =
+        (Vec::with_capacity(capacity), Vec::with_capacity(capacity));
+
+    let endian = default_endian.path();
+    let (repr_write, repr_read) = (
+        quote!(#endian::write::<#repr_ty, _>),
+        quote!(#endian::read::<#repr_ty, _>),
+    );
+
+    let cloneable = data
+        .variants
+        .iter()
+        .all(|v| matches!(&v.fields, Fields::Unit));
+
+    for variant in &data.variants {
+        let variant_name = &variant.ident;
+        let discriminant = discriminant(variant)?;
+
+        let fields_patterns = fields::make_patterns(&variant.fields);
+        let fields_write = fields::write(&variant.fields, default_endian, container, seek)?;
+        let fields_read = fields::read(
+            &variant.fields,
+            quote!(Self::#variant_name),
+            default_endian,
+            container,
+        )?;
+
+        write_vars.push(quote!(Self::#variant_name #fields_patterns => {
+            #repr_write(#discriminant, &mut #w)?;
+            #fields_write
+        }));
+        read_vars.push(quote!(#discriminant => #fields_read));
+    }
+
+    let write = if cloneable {
+        quote! {
+            #repr_write(*self as #repr_ty, &mut #w)?;
+        }
+    } else {
+        quote! {
+            match self {
+                #(#write_vars),*
+            }
         }
     };
 
+    let read = quote! {{
+        match #repr_read(&mut #r)? {
+            #(#read_vars,)*
+            _ => Err(::std::io::Error::from(::std::io::ErrorKind::InvalidData))?,
+        }
+    }};
+
+    Ok((write, read))
+}
+
+/// Writes and checks `#[io(magic = ...)]` before the fields, integer magic uses the default endian
+fn magic(
+    container: &ContainerArgs,
+    default_endian: DefaultEndian,
+) -> Result<(TokenStream, TokenStream)> {
+    let (w, r) = (fields::writer(), fields::reader());
+
+    Ok(match &container.magic {
+        Some(Lit::Int(int)) => {
+            if let DefaultEndian::From(mark) = default_endian {
+                return Err(Error::new_spanned(
+                    mark,
+                    "integer magic cannot be used with byte order mark, use a byte string",
+                ));
+            }
+            let endian = default_endian.path();
+            let ty = Ident::new(int.suffix(), int.span());
+            (
+                quote!(#endian::write::<#ty, _>(#int, &mut #w)?;),
+                quote!(::endiannezz::internal::check_magic_int::<#endian, #ty, _>(&mut #r, #int)?;),
+            )
+        }
+        Some(magic) => (
+            quote!(::std::io::Write::write_all(&mut #w, #magic)?;),
+            quote!(::endiannezz::internal::check_magic(&mut #r, #magic)?;),
+        ),
+        None => (TokenStream::new(), TokenStream::new()),
+    })
+}
+
+/// Enums with unit variants only can be packed into `#[bits(n)]` fields
+fn enum_bit_field(input: &DeriveInput, data: &DataEnum, repr_ty: &Ident) -> Result<TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    if !data
+        .variants
+        .iter()
+        .all(|v| matches!(&v.fields, Fields::Unit))
+    {
+        return Ok(TokenStream::new());
+    }
+
+    let names = data.variants.iter().map(|variant| &variant.ident);
+    let discriminants = data
+        .variants
+        .iter()
+        .map(discriminant)
+        .collect::<Result<Vec<_>>>()?;
+    let (names, discriminants) = (names.collect::<Vec<_>>(), &discriminants);
+
     Ok(quote! {
         #[automatically_derived]
-        impl #impl_generics ::endiannezz::Io for #name #ty_generics #where_clause {
-            fn write<W: ::std::io::Write>(&self, mut w: W) -> ::std::io::Result<()> {
-                #imports
-                #write
-                Ok(())
+        impl #impl_generics ::endiannezz::bits::BitField for #name #ty_generics #where_clause {
+            fn to_field(&self, n: u32) -> Option<u128> {
+                let value: #repr_ty = match self {
+                    #(Self::#names => #discriminants,)*
+                };
+                ::endiannezz::bits::BitField::to_field(&value, n)
             }
 
-            fn read<R: ::std::io::Read>(mut r: R) -> ::std::io::Result<Self> {
-                #imports
-                Ok(#read)
+            fn from_field(bits: u128, n: u32) -> Option<Self> {
+                match <#repr_ty as ::endiannezz::bits::BitField>::from_field(bits, n)? {
+                    #(#discriminants => Some(Self::#names),)*
+                    _ => None,
+                }
             }
         }
     })
 }
+
+fn discriminant(variant: &syn::Variant) -> Result<&syn::Expr> {
+    variant
+        .discriminant
+        .as_ref()
+        .map(|(_, discriminant)| discriminant)
+        .ok_or_else(|| {
+            Error::new_spanned(
+                variant,
+                "All enum variants must have explicit discriminants",
+            )
+        })
+}
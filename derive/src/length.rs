@@ -0,0 +1,172 @@
+use crate::attr::io::FieldArgs;
+use crate::bitfield::Item;
+use crate::checksum::Coverage;
+use crate::fields::{self, DefaultEndian};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use syn::{Error, Field, Fields, Result};
+
+/// Field with `#[io(len_of = ...)]` along with the field it measures
+pub struct Length<'a> {
+    /// Name of the measured field as written in the attribute
+    target: Ident,
+    pub field: Ident,
+    len: &'a Field,
+    pub body: Ident,
+    measured: &'a Field,
+    /// Local that holds the bytes of the measured field, or the position of the length if it's
+    /// patched by seeking back
+    local: Ident,
+    seek: bool,
+}
+
+/// Whether some of fields are measured by `#[io(len_of = ...)]`
+pub fn measured(fields: &Fields) -> Result<bool> {
+    for field in fields {
+        if FieldArgs::parse(&field.attrs)?.len_of.is_some() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Finds fields with `#[io(len_of = ...)]`, lengths are patched in place if `seek` is set,
+/// unless the fields are covered by a checksum that can't be taken back
+pub fn lengths<'a>(
+    fields: &'a Fields,
+    items: &[Item],
+    checksums: &[Coverage],
+    seek: bool,
+) -> Result<Vec<Length<'a>>> {
+    let bindings = fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| fields::binding(i, field))
+        .collect::<Vec<_>>();
+
+    let all = fields.iter().collect::<Vec<_>>();
+    let mut lengths = Vec::new();
+    for (i, len) in all.iter().copied().enumerate() {
+        let target = match FieldArgs::parse(&len.attrs)?.len_of {
+            Some(target) => target,
+            None => continue,
+        };
+
+        let j = match bindings.iter().position(|binding| *binding == target) {
+            Some(j) if j > i => j,
+            _ => {
+                return Err(Error::new_spanned(
+                    target,
+                    "len_of must refer to one of the following fields",
+                ))
+            }
+        };
+
+        let (field, body) = (bindings[i].clone(), bindings[j].clone());
+        let packed = items.iter().any(|item| match item {
+            Item::Bitfield(unit) => unit
+                .bindings()
+                .any(|binding| *binding == field || *binding == body),
+            Item::Field(..) => false,
+        });
+        if packed {
+            return Err(Error::new_spanned(
+                target,
+                "len_of cannot be used in bitfield",
+            ));
+        }
+
+        let covered = checksums
+            .iter()
+            .any(|coverage| coverage.covered.contains(&field) || coverage.covered.contains(&body));
+        lengths.push(Length {
+            local: Ident::new(&format!("len_of_{}", i), Span::mixed_site()),
+            target,
+            field,
+            len,
+            body,
+            measured: all[j],
+            seek: seek && !covered,
+        });
+    }
+    Ok(lengths)
+}
+
+/// Declares locals that are assigned when the length field is written
+pub fn locals(lengths: &[Length]) -> TokenStream {
+    let locals = lengths.iter().map(|length| &length.local);
+    quote!(#(let #locals;)*)
+}
+
+/// Writes the length field, the measured field is either buffered before it
+/// or the position is remembered to patch it later
+pub fn write_len(
+    length: &Length,
+    items: &[Item],
+    default_endian: DefaultEndian,
+    write: TokenStream,
+) -> Result<TokenStream> {
+    //the measured field must be written with the same endian to be measured up front
+    let same_section = items
+        .iter()
+        .any(|item| item.bindings().contains(&length.body));
+    if !same_section {
+        return Err(Error::new_spanned(
+            &length.target,
+            "len_of cannot measure fields after byte order mark",
+        ));
+    }
+
+    let (w, local) = (fields::writer(), &length.local);
+    let start = Ident::new("start", Span::mixed_site());
+    if length.seek {
+        return Ok(quote! {
+            let #start = ::std::io::Seek::stream_position(&mut #w)?;
+            #write
+            #local = (#start, ::std::io::Seek::stream_position(&mut #w)?);
+        });
+    }
+
+    let (name, ty) = (&length.field, &length.len.ty);
+    let body = length.body.to_string();
+    let measured = fields::write_field(&length.body, length.measured, default_endian)?;
+    Ok(quote! {
+        #local = {
+            let mut #w = ::std::vec::Vec::new();
+            #measured
+            #w
+        };
+        let #name: &#ty = &::endiannezz::internal::len_of(#local.len() as u64, #body)?;
+        #write
+    })
+}
+
+/// Writes the measured field, then seeks back and writes its length if the writer is seekable
+pub fn write_body(
+    length: &Length,
+    default_endian: DefaultEndian,
+    write: TokenStream,
+) -> Result<TokenStream> {
+    let (w, local) = (fields::writer(), &length.local);
+    if !length.seek {
+        return Ok(quote!(::std::io::Write::write_all(&mut #w, &#local)?;));
+    }
+
+    let (name, ty) = (&length.field, &length.len.ty);
+    let (field, body) = (name.to_string(), length.body.to_string());
+    let (start, end) = (
+        Ident::new("start", Span::mixed_site()),
+        Ident::new("end", Span::mixed_site()),
+    );
+    let len = fields::write_field(name, length.len, default_endian)?;
+    Ok(quote! {
+        let #start = ::std::io::Seek::stream_position(&mut #w)?;
+        #write
+        let #end = ::std::io::Seek::stream_position(&mut #w)?;
+        ::endiannezz::internal::patch(&mut #w, #local, #end, #field, |mut #w| {
+            let #name: &#ty = &::endiannezz::internal::len_of(#end - #start, #body)?;
+            #len
+            Ok(())
+        })?;
+    })
+}
@@ -2,10 +2,13 @@ use proc_macro::TokenStream;
 use syn::{parse_macro_input, DeriveInput};
 
 mod attr;
+mod bitfield;
+mod checksum;
 mod fields;
 mod io;
+mod length;
 
-#[proc_macro_derive(Io, attributes(endian))]
+#[proc_macro_derive(Io, attributes(bits, endian, io))]
 pub fn derive_io(input: TokenStream) -> TokenStream {
     io::derive(parse_macro_input!(input as DeriveInput))
         .unwrap_or_else(|err| err.to_compile_error())
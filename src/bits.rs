@@ -0,0 +1,465 @@
+use crate::ext::{EndianReader, EndianWriter};
+use crate::{NativeEndian, Primitive};
+use std::convert::TryFrom;
+use std::io::{Error, ErrorKind, Read, Result, Write};
+use std::marker::PhantomData;
+
+/// Order of bits inside of a byte
+pub trait BitOrder {
+    /// Returns bit of `byte` that goes `index`-th in the stream
+    fn get(byte: u8, index: u32) -> bool;
+
+    /// Sets bit of `byte` that goes `index`-th in the stream
+    fn set(byte: u8, index: u32, bit: bool) -> u8;
+
+    /// Returns bit of `n`-bit value that goes `index`-th in the stream
+    fn get_value(value: u128, index: u32, n: u32) -> bool;
+
+    /// Sets bit of `n`-bit value that goes `index`-th in the stream
+    fn set_value(value: u128, index: u32, n: u32, bit: bool) -> u128;
+}
+
+/// The most significant bit goes first, both in bytes and values.
+/// Used by most network protocols and video codecs
+pub enum MsbFirst {}
+
+/// The least significant bit goes first, both in bytes and values. Used by DEFLATE
+pub enum LsbFirst {}
+
+impl BitOrder for MsbFirst {
+    #[inline]
+    fn get(byte: u8, index: u32) -> bool {
+        byte >> (7 - index) & 1 != 0
+    }
+
+    #[inline]
+    fn set(byte: u8, index: u32, bit: bool) -> u8 {
+        byte | (bit as u8) << (7 - index)
+    }
+
+    #[inline]
+    fn get_value(value: u128, index: u32, n: u32) -> bool {
+        value >> (n - 1 - index) & 1 != 0
+    }
+
+    #[inline]
+    fn set_value(value: u128, index: u32, n: u32, bit: bool) -> u128 {
+        value | (bit as u128) << (n - 1 - index)
+    }
+}
+
+impl BitOrder for LsbFirst {
+    #[inline]
+    fn get(byte: u8, index: u32) -> bool {
+        byte >> index & 1 != 0
+    }
+
+    #[inline]
+    fn set(byte: u8, index: u32, bit: bool) -> u8 {
+        byte | (bit as u8) << index
+    }
+
+    #[inline]
+    fn get_value(value: u128, index: u32, _n: u32) -> bool {
+        value >> index & 1 != 0
+    }
+
+    #[inline]
+    fn set_value(value: u128, index: u32, _n: u32, bit: bool) -> u128 {
+        value | (bit as u128) << index
+    }
+}
+
+/// Integer primitives that can be read from or written into a number of bits
+///
+/// Signed integers use two's complement, so 3 bits hold values from -4 to 3
+pub trait Bits: Primitive {
+    const BITS: u32;
+
+    /// Returns `None` if the value does not fit into `n` bits
+    fn to_bits(self, n: u32) -> Option<u128>;
+
+    fn from_bits(bits: u128, n: u32) -> Self;
+}
+
+macro_rules! impl_unsigned_bits {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Bits for $ty {
+                const BITS: u32 = <$ty>::BITS;
+
+                #[inline]
+                fn to_bits(self, n: u32) -> Option<u128> {
+                    let bits = self as u128;
+                    match bits.checked_shr(n) {
+                        Some(rest) if rest != 0 => None,
+                        _ => Some(bits),
+                    }
+                }
+
+                #[inline]
+                fn from_bits(bits: u128, _n: u32) -> Self {
+                    bits as $ty
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_signed_bits {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Bits for $ty {
+                const BITS: u32 = <$ty>::BITS;
+
+                #[inline]
+                fn to_bits(self, n: u32) -> Option<u128> {
+                    let value = self as i128;
+                    if n == 0 {
+                        return if value == 0 { Some(0) } else { None };
+                    }
+
+                    //the value must survive truncation to `n` bits and sign extension back
+                    let shift = 128 - n;
+                    if value << shift >> shift == value {
+                        Some((value as u128) << shift >> shift)
+                    } else {
+                        None
+                    }
+                }
+
+                #[inline]
+                fn from_bits(bits: u128, n: u32) -> Self {
+                    match n {
+                        0 => 0,
+                        _ => ((bits << (128 - n)) as i128 >> (128 - n)) as $ty,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_unsigned_bits![u8, u16, u32, u64, u128, usize];
+impl_signed_bits![i8, i16, i32, i64, i128, isize];
+
+/// Values of `#[bits(n)]` fields in derived bitfield structs
+///
+/// Implemented for integers and `bool`, `#[derive(Io)]` implements it for enums
+/// that have only unit variants.
+pub trait BitField: Sized {
+    /// Returns `None` if the value does not fit into `n` bits
+    fn to_field(&self, n: u32) -> Option<u128>;
+
+    /// Returns `None` if `n` bits don't hold a valid value
+    fn from_field(bits: u128, n: u32) -> Option<Self>;
+}
+
+macro_rules! impl_bit_fields {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl BitField for $ty {
+                #[inline]
+                fn to_field(&self, n: u32) -> Option<u128> {
+                    self.to_bits(n)
+                }
+
+                #[inline]
+                fn from_field(bits: u128, n: u32) -> Option<Self> {
+                    //wider fields than the type are allowed while the value survives the round trip
+                    let value = <$ty>::from_bits(bits, n);
+                    match value.to_bits(n) {
+                        Some(back) if back == bits => Some(value),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+#[rustfmt::skip]
+impl_bit_fields![
+    i8, i16, i32, i64, i128, isize,
+    u8, u16, u32, u64, u128, usize,
+];
+
+impl BitField for bool {
+    #[inline]
+    fn to_field(&self, n: u32) -> Option<u128> {
+        match n {
+            0 => None,
+            _ => Some(*self as u128),
+        }
+    }
+
+    #[inline]
+    fn from_field(bits: u128, _n: u32) -> Option<Self> {
+        match bits {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+fn check_width<T: Bits>(n: u32) -> Result<()> {
+    if n <= T::BITS {
+        Ok(())
+    } else {
+        Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} bits do not fit into {}-bit integer", n, T::BITS),
+        ))
+    }
+}
+
+/// Reads values bit by bit from the underlying reader
+///
+/// Bytes are taken from the reader only when the next bit is needed.
+/// `BitReader` implements [`Read`] itself, so [`EndianReader`] methods work on it as well:
+/// they are just as fast as usual when the reader is aligned to a byte boundary.
+///
+/// [`Read`]: https://doc.rust-lang.org/std/io/trait.Read.html
+/// [`EndianReader`]: ../ext/trait.EndianReader.html
+pub struct BitReader<R, O: BitOrder> {
+    inner: R,
+    byte: u8,
+    consumed: u32,
+    order: PhantomData<O>,
+}
+
+impl<R: Read, O: BitOrder> BitReader<R, O> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            byte: 0,
+            consumed: 8,
+            order: PhantomData,
+        }
+    }
+
+    /// Returns `true` if there are no bits left from the last byte
+    #[inline]
+    pub fn is_aligned(&self) -> bool {
+        self.consumed == 8
+    }
+
+    /// Skips the rest of bits up to the byte boundary
+    #[inline]
+    pub fn align(&mut self) {
+        self.consumed = 8;
+    }
+
+    pub fn read_bit(&mut self) -> Result<bool> {
+        if self.is_aligned() {
+            self.byte = self.inner.try_read::<NativeEndian, u8>()?;
+            self.consumed = 0;
+        }
+
+        let bit = O::get(self.byte, self.consumed);
+        self.consumed += 1;
+        Ok(bit)
+    }
+
+    /// Reads `n` bits into `T`, fails if `T` is narrower than `n` bits
+    pub fn read_bits<T: Bits>(&mut self, n: u32) -> Result<T> {
+        check_width::<T>(n)?;
+
+        let mut value = 0;
+        for i in 0..n {
+            value = O::set_value(value, i, n, self.read_bit()?);
+        }
+        Ok(T::from_bits(value, n))
+    }
+
+    /// Reads unsigned exponential-Golomb code, `ue(v)` of H.264
+    pub fn read_exp_golomb(&mut self) -> Result<u32> {
+        let mut zeros = 0;
+        while !self.read_bit()? {
+            zeros += 1;
+            if zeros > 32 {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "exp-Golomb code does not fit into 32 bits",
+                ));
+            }
+        }
+
+        let value = (1_u64 << zeros) - 1 + self.read_bits::<u64>(zeros)?;
+        value.to_bits(32).map(|value| value as u32).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                "exp-Golomb code does not fit into 32 bits",
+            )
+        })
+    }
+
+    /// Reads signed exponential-Golomb code, `se(v)` of H.264
+    pub fn read_signed_exp_golomb(&mut self) -> Result<i32> {
+        let k = i64::from(self.read_exp_golomb()?);
+        let value = if k % 2 == 1 { (k + 1) / 2 } else { -(k / 2) };
+        i32::try_from(value).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidData,
+                "signed exp-Golomb code does not fit into 32 bits",
+            )
+        })
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns the underlying reader, bits that are left from the last byte are lost
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read, O: BitOrder> Read for BitReader<R, O> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if self.is_aligned() {
+            return self.inner.read(buf);
+        }
+
+        match buf.first_mut() {
+            Some(byte) => {
+                *byte = self.read_bits(8)?;
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+}
+
+/// Writes values bit by bit into the underlying writer
+///
+/// A byte is passed to the writer as soon as all of its bits are written, so the
+/// last incomplete byte must be finished by [`align`] or [`into_inner`].
+/// `BitWriter` implements [`Write`] itself, so [`EndianWriter`] methods work on it as well.
+///
+/// [`align`]: #method.align
+/// [`into_inner`]: #method.into_inner
+/// [`Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
+/// [`EndianWriter`]: ../ext/trait.EndianWriter.html
+pub struct BitWriter<W, O: BitOrder> {
+    inner: W,
+    byte: u8,
+    filled: u32,
+    order: PhantomData<O>,
+}
+
+impl<W: Write, O: BitOrder> BitWriter<W, O> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            byte: 0,
+            filled: 0,
+            order: PhantomData,
+        }
+    }
+
+    /// Returns `true` if there is no incomplete byte
+    #[inline]
+    pub fn is_aligned(&self) -> bool {
+        self.filled == 0
+    }
+
+    /// Fills the rest of incomplete byte with zeros and writes it
+    pub fn align(&mut self) -> Result<()> {
+        if !self.is_aligned() {
+            self.inner.try_write::<NativeEndian, u8>(self.byte)?;
+            self.byte = 0;
+            self.filled = 0;
+        }
+        Ok(())
+    }
+
+    pub fn write_bit(&mut self, bit: bool) -> Result<()> {
+        self.byte = O::set(self.byte, self.filled, bit);
+        self.filled += 1;
+
+        if self.filled == 8 {
+            self.align()?;
+        }
+        Ok(())
+    }
+
+    /// Writes `value` as `n` bits, fails if it does not fit into them
+    pub fn write_bits<T: Bits>(&mut self, value: T, n: u32) -> Result<()> {
+        check_width::<T>(n)?;
+
+        let bits = value.to_bits(n).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("value does not fit into {} bits", n),
+            )
+        })?;
+        for i in 0..n {
+            self.write_bit(O::get_value(bits, i, n))?;
+        }
+        Ok(())
+    }
+
+    /// Writes unsigned exponential-Golomb code, `ue(v)` of H.264
+    pub fn write_exp_golomb(&mut self, value: u32) -> Result<()> {
+        let value = u64::from(value) + 1;
+        let len = u64::BITS - value.leading_zeros();
+
+        self.write_bits(0_u64, len - 1)?;
+        self.write_bits(value, len)
+    }
+
+    /// Writes signed exponential-Golomb code, `se(v)` of H.264
+    pub fn write_signed_exp_golomb(&mut self, value: i32) -> Result<()> {
+        let value = i64::from(value);
+        let k = if value > 0 { 2 * value - 1 } else { -2 * value };
+        let k = u32::try_from(k).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} does not fit into signed exp-Golomb code", value),
+            )
+        })?;
+        self.write_exp_golomb(k)
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Aligns the writer and returns the underlying writer
+    pub fn into_inner(mut self) -> Result<W> {
+        self.align()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write, O: BitOrder> Write for BitWriter<W, O> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        if self.is_aligned() {
+            return self.inner.write(buf);
+        }
+
+        match buf.first() {
+            Some(&byte) => {
+                self.write_bits(byte, 8)?;
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+
+    /// Flushes the underlying writer, incomplete byte is kept
+    fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+}
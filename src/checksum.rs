@@ -0,0 +1,239 @@
+use crate::Primitive;
+use std::io::{Read, Result, Write};
+
+/// Algorithm that digests bytes into a value, which is usually stored after them
+///
+/// It's implemented for `&mut C` too, so adapters can feed a checksum that is owned elsewhere.
+pub trait Checksum {
+    type Output: Primitive;
+
+    /// Digests the next chunk of bytes
+    fn update(&mut self, bytes: &[u8]);
+
+    /// Returns the checksum of all bytes digested so far
+    fn finish(&self) -> Self::Output;
+}
+
+impl<C: Checksum + ?Sized> Checksum for &mut C {
+    type Output = C::Output;
+
+    #[inline]
+    fn update(&mut self, bytes: &[u8]) {
+        (**self).update(bytes)
+    }
+
+    #[inline]
+    fn finish(&self) -> Self::Output {
+        (**self).finish()
+    }
+}
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < table.len() {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+static CRC32_TABLE: [u32; 256] = crc32_table();
+
+/// CRC-32 (ISO-HDLC) that is used by PNG, Ethernet, zip and gzip
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc32 {
+    state: u32,
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self { state: !0 }
+    }
+}
+
+impl Checksum for Crc32 {
+    type Output = u32;
+
+    fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            let index = (self.state ^ u32::from(byte)) & 0xff;
+            self.state = (self.state >> 8) ^ CRC32_TABLE[index as usize];
+        }
+    }
+
+    #[inline]
+    fn finish(&self) -> u32 {
+        !self.state
+    }
+}
+
+const ADLER32_MODULUS: u32 = 65521;
+
+/// Number of bytes that can be summed before `b` of Adler-32 may overflow
+const ADLER32_CHUNK: usize = 5552;
+
+/// Adler-32 that is used by zlib
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Default for Adler32 {
+    fn default() -> Self {
+        Self { a: 1, b: 0 }
+    }
+}
+
+impl Checksum for Adler32 {
+    type Output = u32;
+
+    fn update(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(ADLER32_CHUNK) {
+            for &byte in chunk {
+                self.a += u32::from(byte);
+                self.b += self.a;
+            }
+            self.a %= ADLER32_MODULUS;
+            self.b %= ADLER32_MODULUS;
+        }
+    }
+
+    #[inline]
+    fn finish(&self) -> u32 {
+        self.b << 16 | self.a
+    }
+}
+
+macro_rules! impl_sums {
+    ($($(#[$attr:meta])* $name:ident $ty:ty,)*) => {
+        $(
+            $(#[$attr])*
+            #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+            pub struct $name {
+                sum: $ty,
+            }
+
+            impl Checksum for $name {
+                type Output = $ty;
+
+                fn update(&mut self, bytes: &[u8]) {
+                    for &byte in bytes {
+                        self.sum = self.sum.wrapping_add(<$ty>::from(byte));
+                    }
+                }
+
+                #[inline]
+                fn finish(&self) -> $ty {
+                    self.sum
+                }
+            }
+        )*
+    };
+}
+
+impl_sums![
+    /// Sum of bytes modulo 2^8
+    Sum8 u8,
+    /// Sum of bytes modulo 2^16
+    Sum16 u16,
+    /// Sum of bytes modulo 2^32
+    Sum32 u32,
+];
+
+/// Reader that digests every byte which is read through it
+pub struct ChecksumReader<R, C> {
+    inner: R,
+    checksum: C,
+}
+
+impl<R: Read, C: Checksum> ChecksumReader<R, C> {
+    pub fn new(inner: R) -> Self
+    where
+        C: Default,
+    {
+        Self::with_checksum(inner, C::default())
+    }
+
+    pub fn with_checksum(inner: R, checksum: C) -> Self {
+        Self { inner, checksum }
+    }
+
+    /// Returns the checksum of bytes read so far
+    #[inline]
+    pub fn checksum(&self) -> C::Output {
+        self.checksum.finish()
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read, C: Checksum> Read for ChecksumReader<R, C> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.checksum.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// Writer that digests every byte which is written through it
+pub struct ChecksumWriter<W, C> {
+    inner: W,
+    checksum: C,
+}
+
+impl<W: Write, C: Checksum> ChecksumWriter<W, C> {
+    pub fn new(inner: W) -> Self
+    where
+        C: Default,
+    {
+        Self::with_checksum(inner, C::default())
+    }
+
+    pub fn with_checksum(inner: W, checksum: C) -> Self {
+        Self { inner, checksum }
+    }
+
+    /// Returns the checksum of bytes written so far
+    #[inline]
+    pub fn checksum(&self) -> C::Output {
+        self.checksum.finish()
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write, C: Checksum> Write for ChecksumWriter<W, C> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.checksum.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+}
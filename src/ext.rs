@@ -1,5 +1,6 @@
-use crate::{Endian, Primitive};
-use std::io::{Read, Result, Write};
+use crate::{Endian, Length, Primitive};
+use std::any;
+use std::io::{Error, ErrorKind, Read, Result, Write};
 
 /// Allows to write primitive types with different representation of bytes
 pub trait EndianWriter: Write {
@@ -22,6 +23,18 @@ pub trait EndianWriter: Write {
     fn write_be<T: Primitive>(&mut self, primitive: T) -> Result<()> {
         self.try_write::<crate::BigEndian, T>(primitive)
     }
+
+    /// Writes length of collection as `L`, fails if it does not fit into the prefix
+    #[inline]
+    fn write_len<E: Endian, L: Length>(&mut self, len: usize) -> Result<()> {
+        let prefix = L::from_usize(len).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("length {} does not fit into {}", len, any::type_name::<L>()),
+            )
+        })?;
+        self.try_write::<E, L>(prefix)
+    }
 }
 
 impl<W: Write + ?Sized> EndianWriter for W {}
@@ -47,6 +60,17 @@ pub trait EndianReader: Read {
     fn read_be<T: Primitive>(&mut self) -> Result<T> {
         self.try_read::<crate::BigEndian, T>()
     }
+
+    /// Reads length of collection that was written by [`EndianWriter::write_len`]
+    #[inline]
+    fn read_len<E: Endian, L: Length>(&mut self) -> Result<usize> {
+        self.try_read::<E, L>()?.to_usize().ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid length prefix of type {}", any::type_name::<L>()),
+            )
+        })
+    }
 }
 
 impl<R: Read + ?Sized> EndianReader for R {}
@@ -0,0 +1,160 @@
+use crate::{Endian, Io, IoEndian};
+use std::convert::TryFrom;
+use std::io::{Read, Result, Write};
+
+/// Builds an array by calling `f` for each element in order
+pub fn read_array<T, F, const N: usize>(mut f: F) -> Result<[T; N]>
+where
+    F: FnMut() -> Result<T>,
+{
+    let mut vec = Vec::with_capacity(N);
+    for _ in 0..N {
+        vec.push(f()?);
+    }
+
+    match <[T; N]>::try_from(vec) {
+        Ok(array) => Ok(array),
+        Err(_) => unreachable!("vec always has exactly N elements"),
+    }
+}
+
+/// Upper bound of elements which are allocated before reading a collection
+const PREALLOCATE: usize = 4096;
+
+/// Builds a vec by calling `f` for each of `len` elements in order
+pub fn read_vec<T, F>(len: usize, mut f: F) -> Result<Vec<T>>
+where
+    F: FnMut() -> Result<T>,
+{
+    //`len` usually comes from the input, so a corrupted prefix must not trigger huge allocation
+    let mut vec = Vec::with_capacity(len.min(PREALLOCATE));
+    for _ in 0..len {
+        vec.push(f()?);
+    }
+    Ok(vec)
+}
+
+/// Elements are written one after another without any length prefix
+impl<T: Io, const N: usize> Io for [T; N] {
+    #[cfg_attr(feature = "inline_io", inline(always))]
+    fn write<W: Write>(&self, mut w: W) -> Result<()> {
+        for item in self {
+            item.write(&mut w)?;
+        }
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "inline_io", inline(always))]
+    fn read<R: Read>(mut r: R) -> Result<Self> {
+        read_array(|| T::read(&mut r))
+    }
+}
+
+impl<T: IoEndian, const N: usize> IoEndian for [T; N] {
+    #[cfg_attr(feature = "inline_io", inline(always))]
+    fn write_endian<E: Endian, W: Write>(&self, mut w: W) -> Result<()> {
+        for item in self {
+            item.write_endian::<E, _>(&mut w)?;
+        }
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "inline_io", inline(always))]
+    fn read_endian<E: Endian, R: Read>(mut r: R) -> Result<Self> {
+        read_array(|| T::read_endian::<E, _>(&mut r))
+    }
+}
+
+macro_rules! impl_tuples {
+    ($(($($name:ident $index:tt),+),)*) => {
+        $(
+            /// Elements are written one after another in declaration order
+            impl<$($name: Io),+> Io for ($($name,)+) {
+                #[cfg_attr(feature = "inline_io", inline(always))]
+                fn write<W: Write>(&self, mut w: W) -> Result<()> {
+                    $(self.$index.write(&mut w)?;)+
+                    Ok(())
+                }
+
+                #[cfg_attr(feature = "inline_io", inline(always))]
+                fn read<R: Read>(mut r: R) -> Result<Self> {
+                    Ok(($($name::read(&mut r)?,)+))
+                }
+            }
+
+            impl<$($name: IoEndian),+> IoEndian for ($($name,)+) {
+                #[cfg_attr(feature = "inline_io", inline(always))]
+                fn write_endian<E: Endian, W: Write>(&self, mut w: W) -> Result<()> {
+                    $(self.$index.write_endian::<E, _>(&mut w)?;)+
+                    Ok(())
+                }
+
+                #[cfg_attr(feature = "inline_io", inline(always))]
+                fn read_endian<E: Endian, R: Read>(mut r: R) -> Result<Self> {
+                    Ok(($($name::read_endian::<E, _>(&mut r)?,)+))
+                }
+            }
+        )*
+    };
+}
+
+#[rustfmt::skip]
+impl_tuples![
+    (T0 0),
+    (T0 0, T1 1),
+    (T0 0, T1 1, T2 2),
+    (T0 0, T1 1, T2 2, T3 3),
+    (T0 0, T1 1, T2 2, T3 3, T4 4),
+    (T0 0, T1 1, T2 2, T3 3, T4 4, T5 5),
+    (T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6),
+    (T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7),
+    (T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8),
+    (T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9),
+    (T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10),
+    (T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11),
+];
+
+/// Value is preceded by `bool` presence flag
+impl<T: Io> Io for Option<T> {
+    #[cfg_attr(feature = "inline_io", inline(always))]
+    fn write<W: Write>(&self, mut w: W) -> Result<()> {
+        match self {
+            Some(value) => {
+                true.write(&mut w)?;
+                value.write(w)
+            }
+            None => false.write(w),
+        }
+    }
+
+    #[cfg_attr(feature = "inline_io", inline(always))]
+    fn read<R: Read>(mut r: R) -> Result<Self> {
+        if bool::read(&mut r)? {
+            Ok(Some(T::read(r)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T: IoEndian> IoEndian for Option<T> {
+    #[cfg_attr(feature = "inline_io", inline(always))]
+    fn write_endian<E: Endian, W: Write>(&self, mut w: W) -> Result<()> {
+        match self {
+            Some(value) => {
+                true.write(&mut w)?;
+                value.write_endian::<E, _>(w)
+            }
+            None => false.write(w),
+        }
+    }
+
+    #[cfg_attr(feature = "inline_io", inline(always))]
+    fn read_endian<E: Endian, R: Read>(mut r: R) -> Result<Self> {
+        if bool::read(&mut r)? {
+            Ok(Some(T::read_endian::<E, _>(r)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
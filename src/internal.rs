@@ -1,5 +1,12 @@
-use crate::{Endian, Io, Primitive};
-use std::io::{Read, Result, Write};
+use crate::bits::BitField;
+use crate::checksum::{Checksum, ChecksumReader, ChecksumWriter};
+use crate::{Endian, Io, IoEndian, Primitive};
+use std::convert::TryFrom;
+use std::fmt::{Display, LowerHex};
+use std::io::{Error, ErrorKind, Read, Result, Seek, SeekFrom, Take, Write};
+use std::marker::PhantomData;
+
+pub use crate::impls::{read_array, read_vec};
 
 pub trait HackedPrimitive: Primitive {
     #[cfg_attr(feature = "inline_primitives", inline)]
@@ -28,3 +35,347 @@ pub trait HackedIo: Io {
 }
 
 impl<T: Io> HackedIo for T {}
+
+/// Dispatches fields with `#[endian]`, it's called as `(&&Forced::<T>::new()).write_forced(..)`
+///
+/// [`IoEndian`] is preferred, because its impl is for `&Forced<T>` and matches the receiver
+/// without dereferencing. Types that implement only [`Io`] ignore the byte order.
+pub struct Forced<T>(PhantomData<T>);
+
+impl<T> Forced<T> {
+    #[allow(clippy::new_without_default)]
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+pub trait ForcedEndian<T> {
+    fn write_forced<E: Endian, W: Write>(&self, value: &T, w: W) -> Result<()>;
+
+    fn read_forced<E: Endian, R: Read>(&self, r: R) -> Result<T>;
+}
+
+impl<T: IoEndian> ForcedEndian<T> for &Forced<T> {
+    #[cfg_attr(feature = "inline_io", inline(always))]
+    fn write_forced<E: Endian, W: Write>(&self, value: &T, w: W) -> Result<()> {
+        value.write_endian::<E, _>(w)
+    }
+
+    #[cfg_attr(feature = "inline_io", inline(always))]
+    fn read_forced<E: Endian, R: Read>(&self, r: R) -> Result<T> {
+        T::read_endian::<E, _>(r)
+    }
+}
+
+pub trait ForcedIo<T> {
+    fn write_forced<E: Endian, W: Write>(&self, value: &T, w: W) -> Result<()>;
+
+    fn read_forced<E: Endian, R: Read>(&self, r: R) -> Result<T>;
+}
+
+impl<T: Io> ForcedIo<T> for Forced<T> {
+    #[cfg_attr(feature = "inline_io", inline(always))]
+    fn write_forced<E: Endian, W: Write>(&self, value: &T, w: W) -> Result<()> {
+        value.write(w)
+    }
+
+    #[cfg_attr(feature = "inline_io", inline(always))]
+    fn read_forced<E: Endian, R: Read>(&self, r: R) -> Result<T> {
+        T::read(r)
+    }
+}
+
+pub trait ForcedPrimitive<T> {
+    fn write_forced<E: Endian, W: Write>(&self, value: &T, w: W) -> Result<()>;
+
+    fn read_forced<E: Endian, R: Read>(&self, r: R) -> Result<T>;
+}
+
+impl<T: Primitive> ForcedPrimitive<T> for Forced<T> {
+    #[cfg_attr(feature = "inline_primitives", inline)]
+    fn write_forced<E: Endian, W: Write>(&self, value: &T, w: W) -> Result<()> {
+        E::write(*value, w)
+    }
+
+    #[cfg_attr(feature = "inline_primitives", inline)]
+    fn read_forced<E: Endian, R: Read>(&self, r: R) -> Result<T> {
+        E::read(r)
+    }
+}
+
+/// Returns bits of `#[bits(n)]` field, fails if the value does not fit into them
+pub fn pack_field<T: BitField>(value: &T, n: u32, name: &str) -> Result<u128> {
+    value.to_field(n).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("`{}` does not fit into {} bits", name, n),
+        )
+    })
+}
+
+/// Builds value of `#[bits(n)]` field, fails if the bits don't hold a valid value
+pub fn unpack_field<T: BitField>(bits: u128, n: u32, name: &str) -> Result<T> {
+    T::from_field(bits, n).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("invalid value {:#x} of `{}`", bits, name),
+        )
+    })
+}
+
+/// Reads magic of `#[io(magic = b"...")]`, fails if the bytes are different
+pub fn check_magic<R: Read>(mut r: R, expected: &[u8]) -> Result<()> {
+    let mut actual = vec![0; expected.len()];
+    r.read_exact(&mut actual)?;
+
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "invalid magic, expected {:02x?}, found {:02x?}",
+                expected, actual
+            ),
+        ))
+    }
+}
+
+/// Reads magic of `#[io(magic = 0xCAFEBABE_u32)]` encoded in the given endian
+pub fn check_magic_int<E: Endian, T: Primitive, R: Read>(r: R, expected: T) -> Result<()> {
+    let mut buf = T::Buf::default();
+    E::write(expected, buf.as_mut())?;
+    check_magic(r, buf.as_ref())
+}
+
+/// Reader or writer that counts bytes passed through it, used by `#[io(align = n)]`
+pub struct Position<T> {
+    inner: T,
+    position: u64,
+}
+
+impl<T> Position<T> {
+    pub fn new(inner: T) -> Self {
+        Self { inner, position: 0 }
+    }
+}
+
+/// Access to the counted position through other adapters, such as checksums
+pub trait Tell {
+    fn position(&self) -> u64;
+}
+
+impl<T> Tell for Position<T> {
+    fn position(&self) -> u64 {
+        self.position
+    }
+}
+
+impl<T: Tell + ?Sized> Tell for &mut T {
+    fn position(&self) -> u64 {
+        (**self).position()
+    }
+}
+
+impl<R: Read + Tell, C: Checksum> Tell for ChecksumReader<R, C> {
+    fn position(&self) -> u64 {
+        self.get_ref().position()
+    }
+}
+
+impl<W: Write + Tell, C: Checksum> Tell for ChecksumWriter<W, C> {
+    fn position(&self) -> u64 {
+        self.get_ref().position()
+    }
+}
+
+impl<R: Read> Read for Position<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+//seeking moves the counted position too, so it stays relative to the start of the type
+impl<T: Seek> Seek for Position<T> {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        let old = self.inner.stream_position()?;
+        let new = self.inner.seek(pos)?;
+        self.position = (self.position + new).wrapping_sub(old);
+        Ok(new)
+    }
+}
+
+impl<W: Write> Write for Position<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Number of bytes between the position of `stream` and the next multiple of `align`
+pub fn align_padding<T: Tell>(stream: &T, align: usize) -> usize {
+    let position = stream.position();
+    match align as u64 {
+        0 | 1 => 0,
+        align => ((align - position % align) % align) as usize,
+    }
+}
+
+const ZEROS: [u8; 64] = [0; 64];
+
+/// Writes `n` zero bytes of padding
+pub fn write_padding<W: Write>(mut w: W, mut n: usize) -> Result<()> {
+    while n > 0 {
+        let chunk = n.min(ZEROS.len());
+        w.write_all(&ZEROS[..chunk])?;
+        n -= chunk;
+    }
+    Ok(())
+}
+
+/// Skips `n` bytes of padding, in strict mode they must be zeros
+pub fn read_padding<R: Read>(mut r: R, mut n: usize, strict: bool) -> Result<()> {
+    let mut buf = ZEROS;
+    while n > 0 {
+        let chunk = &mut buf[..n.min(ZEROS.len())];
+        r.read_exact(chunk)?;
+        if strict && chunk.iter().any(|&byte| byte != 0) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("non-zero padding {:02x?}", chunk),
+            ));
+        }
+        n -= chunk.len();
+    }
+    Ok(())
+}
+
+/// Converts the value with the function of `#[io(map = ...)]`
+pub fn map<T, U, F: FnOnce(T) -> U>(value: T, f: F) -> U {
+    f(value)
+}
+
+/// Converts the value with the function of `#[io(try_map = ...)]`, its error gets the given kind
+pub fn try_map<T, U, E, F>(value: T, f: F, kind: ErrorKind, message: &str) -> Result<U>
+where
+    F: FnOnce(T) -> std::result::Result<U, E>,
+    E: Display,
+{
+    f(value).map_err(|err| Error::new(kind, format!("{}: {}", message, err)))
+}
+
+/// Compares checksum of `#[io(checksum = ...)]` field with the computed one
+pub fn verify_checksum<T: PartialEq + LowerHex>(name: &str, stored: T, computed: T) -> Result<()> {
+    if stored == computed {
+        Ok(())
+    } else {
+        Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "checksum mismatch of `{}`, stored {:#x}, computed {:#x}",
+                name, stored, computed
+            ),
+        ))
+    }
+}
+
+/// Fails if `#[io(size = n)]` field was written with a different number of bytes
+pub fn check_size<T: Tell>(stream: &T, size: usize, name: &str) -> Result<()> {
+    let written = stream.position();
+    if written == size as u64 {
+        Ok(())
+    } else {
+        Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("`{}` takes {} bytes instead of {}", name, written, size),
+        ))
+    }
+}
+
+/// Handles bytes which are left after `#[io(size = n)]` field: they are either skipped,
+/// or rejected if the field must occupy its size exactly
+pub fn skip_trailing<R: Read>(mut r: Take<R>, exact: bool, name: &str) -> Result<()> {
+    let left = r.limit();
+    if left == 0 {
+        return Ok(());
+    }
+    if exact {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("`{}` has {} trailing bytes", name, left),
+        ));
+    }
+    read_padding(&mut r, left as usize, false)
+}
+
+/// Converts length of `#[io(len_of = field)]` to the type of the length field
+pub fn len_of<T: TryFrom<u64>>(len: u64, name: &str) -> Result<T> {
+    T::try_from(len).map_err(|_| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("length of `{}` is too large: {}", name, len),
+        )
+    })
+}
+
+/// Writes the length over its placeholder at `at`, then returns to `end`
+pub fn patch<W, F>(w: &mut W, at: (u64, u64), end: u64, name: &str, f: F) -> Result<()>
+where
+    W: Write + Seek,
+    F: FnOnce(&mut W) -> Result<()>,
+{
+    let (start, placeholder_end) = at;
+    w.seek(SeekFrom::Start(start))?;
+    f(w)?;
+    if w.stream_position()? != placeholder_end {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "`{}` takes a different number of bytes than its placeholder",
+                name
+            ),
+        ));
+    }
+    w.seek(SeekFrom::Start(end)).map(drop)
+}
+
+/// Number of elements in a collection, which is stored somewhere else
+pub trait Count {
+    fn count(self) -> Result<usize>;
+}
+
+macro_rules! impl_counts {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Count for $ty {
+                #[inline]
+                fn count(self) -> Result<usize> {
+                    usize::try_from(self).map_err(|_| {
+                        Error::new(ErrorKind::InvalidData, format!("invalid count {}", self))
+                    })
+                }
+            }
+        )*
+    };
+}
+
+#[rustfmt::skip]
+impl_counts![
+    i8, i16, i32, i64, i128, isize,
+    u8, u16, u32, u64, u128, usize,
+];
+
+impl<T: Count + Copy> Count for &T {
+    #[inline]
+    fn count(self) -> Result<usize> {
+        (*self).count()
+    }
+}
@@ -79,7 +79,8 @@ impl Io for Bytes {
 }
 
 #[derive(Io)]
-//default endian for fields of struct (except custom impl, such as Bytes)
+//default endian for fields of struct (except custom impl, such as Bytes),
+//it's replaced when the struct is a field with its own `#[endian]`
 #[endian(little)]
 //There are 3 types of endianness and they can be written in the `#[endian]` attribute as follows:
 // - NativeEndian: `_`, `ne`, `native`
@@ -136,17 +137,144 @@ fn main() -> Result<()> {
 }
 ```
 
+# Field attributes
+Besides `#[endian]`, fields of a derived type accept `#[io(...)]` with these arguments:
+- `len = u32`: `Vec<T>` or `String` is prefixed with its length of the given [`Length`] type.
+  Elements use the endian of the field
+- `len_endian = be`: overrides the endian of the length prefix
+- `count = expr`: `Vec<T>` has as many elements as `expr` says, usually it's an earlier field.
+  Derived `write` fails if the count does not match the length of the vec
+- `cstr`: `String` is terminated by NUL byte
+- `fixed = 16`: `String` occupies exactly 16 bytes, the rest is filled with zeros
+- `pad = b' '`: changes the byte that fills the rest of a `fixed` string
+- `utf16`: `String` with `len` or `cstr` is encoded as UTF-16 in the endian of the field.
+  Length is counted in code units, terminator is 0x0000
+- `lossy`: unpaired surrogates of `utf16` string are replaced with U+FFFD instead of error
+- `sentinel = 0xffff`: `Option<T>` is written as the given value when it's `None`,
+  instead of `bool` presence flag that is used by default
+- `if = expr`: `Option<T>` is present only when `expr` is true, without presence flag.
+  Derived `write` fails if the condition does not match whether the value is `Some`
+- `varint`: integer is written as LEB128, unsigned or signed depending on its type.
+  Other encodings are chosen as `varint = zigzag` and `varint = vlq`
+- `pad_before = 4`, `pad_after = 4`: zero bytes around the field. They are skipped on read,
+  unless the type is marked with `#[io(strict_padding)]`, which rejects non-zero padding
+- `align = 8`: zero bytes are inserted before the field (after `pad_before`), so it starts at
+  an offset from the start of the type which is a multiple of the given number
+- `assert = expr` or `assert = expr, "message"`: derived `read` fails with `InvalidData` if
+  the predicate is false after the field is read. Being placed on a struct, it's checked after
+  all fields are read. The type marked with `#[io(assert_on_write)]` checks its assertions
+  before anything is written too
+- `wire = u32, map = (read, write)`: the field is written as the `wire` type in the endian of
+  the field. `read` converts the wire value into the field, `write` converts reference to
+  the field back. `try_map` is the same, but both functions return `Result`
+- `with = module`: the field is written by `module::write::<E, _>(&value, w)` and read by
+  `module::read::<E, _>(r)`, where `E` is the endian of the field. It allows foreign types
+  which don't implement [`Io`] to be used in derived types
+- `calc = expr`: derived `write` uses the value of `expr` instead of the stored one, e.g.
+  `calc = items.len() as u16`. Other fields and assertions see the computed value too, while
+  derived `read` reads the field as usual
+- `checksum = crc32`: derived `write` fills the field with checksum of the previous fields,
+  derived `read` fails if the stored checksum is different. Built-in algorithms are `crc32`,
+  `adler32`, `sum8`, `sum16` and `sum32`, other paths name a type that implements
+  [`Checksum`]. `over = "kind..=data"` limits the fields which are covered, including padding
+- `size = expr`: the field occupies exactly `expr` bytes. Derived `read` can't go past them and
+  skips the bytes which are left, so newer versions of a record may append data to it.
+  `trailing = error` rejects leftover bytes instead, derived `write` fails if the field takes
+  a different number of bytes
+- `len_of = field`: derived `write` stores the number of bytes that one of the following fields
+  takes, the measured field is buffered to count them. [`IoSeek::write_seek`] writes the stored
+  value as a placeholder instead and patches it after the field is written, with the endian of
+  the length. Derived `read` reads the length as usual, `size = len` on the measured field
+  limits reading to it
+- `skip`: the field is not written and is initialized with `Default::default()` on read,
+  `skip = expr` initializes it with the given expression instead
+- `args(expr, ...)`: the nested type (or each element of a collection) is written and read
+  through [`IoWith`], the expressions make up its context. `#[endian]` of the field is
+  passed to the nested type as well
+
+Consecutive fields marked with `#[bits(n)]` are packed into an integer, which is declared
+on the type as `#[io(bitfield = u16)]` and uses the endian of the type. Fields go from the most
+significant bit unless `#[io(bit_order = lsb)]` is specified, and must fill the integer
+completely. Integers, `bool` and enums with unit variants can be packed, derived `write` fails
+if a value does not fit into its bits.
+
+A type with `#[io(magic = b"\x89PNG")]` is preceded by the given bytes, derived `read` fails
+if they don't match. An integer magic needs a type suffix, like `#[io(magic = 0xCAFEBABE_u32)]`,
+and is written in the endian of the type.
+
+Expressions in attributes can refer to other fields by their names (`field_0`, `field_1`, ...
+for tuple structs). Fields are always accessible by reference, both in `read` and `write`.
+
+A type declared with `#[io(context(version: u8, size: u16))]` implements [`IoWith`] instead
+of [`Io`], the parameters are accessible by value in expressions of its fields. A parent passes
+them with `#[io(args(*version, *size))]`.
+
+```rust
+use endiannezz::Io;
+
+#[derive(Io)]
+#[endian(little)]
+struct Bytes {
+    #[io(len = u32)]
+    inner: Vec<u8>,
+}
+```
+
+# Byte order of nested types
+A field with `#[endian]` passes its byte order to the nested type through [`IoEndian`],
+so one derived struct can be reused in formats with different byte order. Derived
+implementations use the given order instead of the declared default, fields with their own
+`#[endian]` keep it. Other fields of nested types use their own default. Custom types that
+implement only [`Io`] ignore the byte order.
+
+Types derived with `#[endian(runtime)]` have no default at all, their byte order is chosen
+by [`Endianness`] value, so one definition covers formats like TIFF where it's stored in
+the header. They implement only [`IoEndian`], so they're written by [`Endianness::write_io`]
+and [`Endianness::read_io`], and can be nested only in fields with `#[endian]`.
+
+When the byte order is stored in the data itself, `#[endian(from = expr)]` reads it from the
+fields that are mentioned in `expr`, which must return [`ByteOrderMark`]. These and earlier
+fields must have their own `#[endian]`, unless their encoding doesn't depend on byte order,
+like `u8`, byte arrays or nested types:
+
+```rust
+use endiannezz::{Endianness, Io};
+
+#[derive(Io)]
+#[endian(from = match order {
+    b"II" => Some(Endianness::Little),
+    b"MM" => Some(Endianness::Big),
+    _ => None,
+})]
+struct TiffHeader {
+    order: [u8; 2],
+    magic: u16,
+    offset: u32,
+}
+```
+
 [different endianness]: https://en.wikipedia.org/wiki/Endianness
+[`IoEndian`]: trait.IoEndian.html
+[`Endianness`]: enum.Endianness.html
+[`ByteOrderMark`]: trait.ByteOrderMark.html
+[`Endianness::write_io`]: enum.Endianness.html#method.write_io
+[`Endianness::read_io`]: enum.Endianness.html#method.read_io
 [`Primitive`]: trait.Primitive.html
 [`Endian`]: trait.Endian.html
 [`NativeEndian`]: enum.NativeEndian.html
 [`LittleEndian`]: enum.LittleEndian.html
 [`BigEndian`]: enum.BigEndian.html
+[`Length`]: trait.Length.html
+[`Io`]: trait.Io.html
+[`IoWith`]: trait.IoWith.html
+[`Checksum`]: checksum/trait.Checksum.html
+[`IoSeek::write_seek`]: trait.IoSeek.html#tymethod.write_seek
 [`Read`]: https://doc.rust-lang.org/std/io/trait.Read.html
 [`Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
 */
 
-use std::io::{Error, ErrorKind, Read, Result, Write};
+use std::convert::TryFrom;
+use std::io::{Error, ErrorKind, Read, Result, Seek, Write};
 use std::mem;
 
 #[cfg(feature = "derive")]
@@ -168,6 +296,40 @@ pub mod internal;
 /// [`Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
 pub mod ext;
 
+/// Codecs for strings: length-prefixed, NUL-terminated and padded to a fixed width
+///
+/// Strings are UTF-8 unless the function says otherwise, invalid input fails with
+/// [`ErrorKind::InvalidData`]. UTF-16 code units use the byte order passed as [`Endian`].
+///
+/// [`ErrorKind::InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+/// [`Endian`]: ../trait.Endian.html
+pub mod strings;
+
+/// Variable-length integers: LEB128, zigzag and VLQ
+///
+/// Unlike [`Primitive`], these encodings don't depend on endianness, so they are provided
+/// by separate [`VarintReader`] and [`VarintWriter`] extensions.
+///
+/// [`Primitive`]: ../trait.Primitive.html
+/// [`VarintReader`]: trait.VarintReader.html
+/// [`VarintWriter`]: trait.VarintWriter.html
+pub mod varint;
+
+/// Reading and writing of values that don't occupy whole bytes
+pub mod bits;
+
+/// Checksums over encoded bytes: CRC-32, Adler-32 and plain sums
+///
+/// [`ChecksumReader`] and [`ChecksumWriter`] digest bytes while they pass through,
+/// own algorithms are added by implementing [`Checksum`].
+///
+/// [`ChecksumReader`]: struct.ChecksumReader.html
+/// [`ChecksumWriter`]: struct.ChecksumWriter.html
+/// [`Checksum`]: trait.Checksum.html
+pub mod checksum;
+
+mod impls;
+
 /// This trait is implemented for all primitive types that exist in rust,
 /// and allows to read types from bytes or write them into bytes
 //noinspection RsSelfConvention
@@ -224,6 +386,39 @@ impl_primitives![
     f32, f64,
 ];
 
+/// Integer primitives which can be used as a length prefix of collections
+pub trait Length: Primitive {
+    /// Returns `None` if `len` does not fit into the prefix type
+    fn from_usize(len: usize) -> Option<Self>;
+
+    /// Returns `None` if the prefix is negative or does not fit into `usize`
+    fn to_usize(self) -> Option<usize>;
+}
+
+macro_rules! impl_lengths {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Length for $ty {
+                #[inline]
+                fn from_usize(len: usize) -> Option<Self> {
+                    <$ty>::try_from(len).ok()
+                }
+
+                #[inline]
+                fn to_usize(self) -> Option<usize> {
+                    usize::try_from(self).ok()
+                }
+            }
+        )*
+    };
+}
+
+#[rustfmt::skip]
+impl_lengths![
+    i8, i16, i32, i64, i128, isize,
+    u8, u16, u32, u64, u128, usize,
+];
+
 /// Proxy for reading and writing primitive types
 pub trait Endian {
     fn write<T: Primitive, W: Write>(primitive: T, w: W) -> Result<()>;
@@ -259,6 +454,110 @@ impl_endianness![
     BigEndian    to_be_bytes from_be_bytes,
 ];
 
+/// Byte order that is chosen at runtime, for formats that declare it in their header
+///
+/// Unlike [`Endian`] types it's a value, so it can be read from input or stored somewhere.
+/// Types that are derived with `#[endian(runtime)]` are read and written by [`read_io`]
+/// and [`write_io`].
+///
+/// ```rust
+/// use endiannezz::Endianness;
+///
+/// let mut vec = Vec::new();
+/// for &endianness in &[Endianness::Little, Endianness::Big] {
+///     endianness.write(1_u16, &mut vec).unwrap();
+/// }
+/// assert_eq!(vec, &[1, 0, 0, 1]);
+/// ```
+///
+/// [`Endian`]: trait.Endian.html
+/// [`read_io`]: #method.read_io
+/// [`write_io`]: #method.write_io
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+impl Endianness {
+    /// Byte order of the target platform
+    #[cfg(target_endian = "little")]
+    pub const NATIVE: Self = Endianness::Little;
+
+    /// Byte order of the target platform
+    #[cfg(target_endian = "big")]
+    pub const NATIVE: Self = Endianness::Big;
+
+    #[inline]
+    pub fn write<T: Primitive, W: Write>(self, primitive: T, w: W) -> Result<()> {
+        match self {
+            Endianness::Little => LittleEndian::write(primitive, w),
+            Endianness::Big => BigEndian::write(primitive, w),
+        }
+    }
+
+    #[inline]
+    pub fn read<T: Primitive, R: Read>(self, r: R) -> Result<T> {
+        match self {
+            Endianness::Little => LittleEndian::read(r),
+            Endianness::Big => BigEndian::read(r),
+        }
+    }
+
+    /// Writes the value by [`IoEndian::write_endian`] in this byte order
+    ///
+    /// [`IoEndian::write_endian`]: trait.IoEndian.html#tymethod.write_endian
+    #[inline]
+    pub fn write_io<T: IoEndian, W: Write>(self, value: &T, w: W) -> Result<()> {
+        match self {
+            Endianness::Little => value.write_endian::<LittleEndian, W>(w),
+            Endianness::Big => value.write_endian::<BigEndian, W>(w),
+        }
+    }
+
+    /// Reads the value by [`IoEndian::read_endian`] in this byte order
+    ///
+    /// [`IoEndian::read_endian`]: trait.IoEndian.html#tymethod.read_endian
+    #[inline]
+    pub fn read_io<T: IoEndian, R: Read>(self, r: R) -> Result<T> {
+        match self {
+            Endianness::Little => T::read_endian::<LittleEndian, R>(r),
+            Endianness::Big => T::read_endian::<BigEndian, R>(r),
+        }
+    }
+}
+
+/// Value that determines byte order of the following data, such as "II" and "MM" of TIFF
+///
+/// It's used by `#[endian(from = expr)]` of derived types: fields after the ones that are
+/// mentioned in `expr` are read and written in the byte order that `expr` returns.
+/// `Option<Endianness>` can be returned by a `match` on bytes of the mark, `None` means
+/// that the mark is invalid.
+pub trait ByteOrderMark {
+    fn endianness(&self) -> Result<Endianness>;
+}
+
+impl ByteOrderMark for Endianness {
+    #[inline]
+    fn endianness(&self) -> Result<Endianness> {
+        Ok(*self)
+    }
+}
+
+impl ByteOrderMark for Option<Endianness> {
+    #[inline]
+    fn endianness(&self) -> Result<Endianness> {
+        self.ok_or_else(|| Error::new(ErrorKind::InvalidData, "invalid byte order mark"))
+    }
+}
+
+impl<T: ByteOrderMark + ?Sized> ByteOrderMark for &T {
+    #[inline]
+    fn endianness(&self) -> Result<Endianness> {
+        (**self).endianness()
+    }
+}
+
 /// Allows the type to be encoded/decoded using binary format
 pub trait Io: Sized {
     fn write<W: Write>(&self, w: W) -> Result<()>;
@@ -266,6 +565,54 @@ pub trait Io: Sized {
     fn read<R: Read>(r: R) -> Result<Self>;
 }
 
+/// Allows the type to be encoded/decoded in the byte order that is chosen by the caller,
+/// derive uses it for fields with `#[endian]`
+///
+/// `#[derive(Io)]` implements it along with [`Io`], and instead of it for types with
+/// `#[endian(runtime)]`, which have no byte order of their own. Fields of types that
+/// implement only [`Io`] are written as usual, the byte order is ignored for them.
+///
+/// [`Io`]: trait.Io.html
+pub trait IoEndian: Sized {
+    fn write_endian<E: Endian, W: Write>(&self, w: W) -> Result<()>;
+
+    fn read_endian<E: Endian, R: Read>(r: R) -> Result<Self>;
+}
+
+/// Allows the type to be encoded/decoded with information that is stored outside of it,
+/// such as the version of a format or the size of a record from the parent header
+///
+/// `#[derive(Io)]` implements it instead of [`Io`] for types with `#[io(context(...))]`,
+/// the context is a tuple of the declared parameters. Fields with both `#[endian]` and
+/// `#[io(args(...))]` are written by [`write_with_endian`], which ignores the byte order
+/// unless it's overridden, as derive does.
+///
+/// [`Io`]: trait.Io.html
+/// [`write_with_endian`]: #method.write_with_endian
+pub trait IoWith<Ctx>: Sized {
+    fn write_with<W: Write>(&self, w: W, ctx: Ctx) -> Result<()>;
+
+    fn read_with<R: Read>(r: R, ctx: Ctx) -> Result<Self>;
+
+    fn write_with_endian<E: Endian, W: Write>(&self, w: W, ctx: Ctx) -> Result<()> {
+        self.write_with(w, ctx)
+    }
+
+    fn read_with_endian<E: Endian, R: Read>(r: R, ctx: Ctx) -> Result<Self> {
+        Self::read_with(r, ctx)
+    }
+}
+
+/// Writes the type to a seekable stream, so lengths of `#[io(len_of = field)]` are written
+/// after the measured fields by seeking back instead of buffering them
+///
+/// `#[derive(Io)]` implements it along with [`Io`] for types that have such lengths.
+///
+/// [`Io`]: trait.Io.html
+pub trait IoSeek: Io {
+    fn write_seek<W: Write + Seek>(&self, w: W) -> Result<()>;
+}
+
 /// Binary representation of a bool
 impl Io for bool {
     #[cfg_attr(feature = "inline_primitives", inline)]
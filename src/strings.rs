@@ -0,0 +1,195 @@
+use crate::ext::{EndianReader, EndianWriter};
+use crate::impls::read_vec;
+use crate::{Endian, Io, Length, NativeEndian};
+use std::char;
+use std::ffi::CString;
+use std::io::{Error, ErrorKind, Read, Result, Write};
+
+/// Decides what happens with unpaired surrogates while decoding UTF-16
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Surrogates {
+    /// Fail with [`ErrorKind::InvalidData`]
+    ///
+    /// [`ErrorKind::InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    Reject,
+    /// Replace each of them with U+FFFD REPLACEMENT CHARACTER
+    Replace,
+}
+
+fn from_utf8(bytes: Vec<u8>) -> Result<String> {
+    String::from_utf8(bytes).map_err(|err| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("string is not valid UTF-8: {}", err),
+        )
+    })
+}
+
+fn read_until_nul<R: Read>(mut r: R) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    loop {
+        match r.try_read::<NativeEndian, u8>()? {
+            0 => return Ok(bytes),
+            byte => bytes.push(byte),
+        }
+    }
+}
+
+/// Writes UTF-8 string prefixed with its length in bytes
+pub fn write_prefixed<E: Endian, L: Length, W: Write>(s: &str, mut w: W) -> Result<()> {
+    w.write_len::<E, L>(s.len())?;
+    w.write_all(s.as_bytes())
+}
+
+/// Reads UTF-8 string that was written by [`write_prefixed`]
+pub fn read_prefixed<E: Endian, L: Length, R: Read>(mut r: R) -> Result<String> {
+    let len = r.read_len::<E, L>()?;
+    let mut bytes = Vec::new();
+    r.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(Error::from(ErrorKind::UnexpectedEof));
+    }
+    from_utf8(bytes)
+}
+
+/// Writes UTF-8 string followed by NUL byte, fails if the string contains NUL itself
+pub fn write_nul_terminated<W: Write>(s: &str, mut w: W) -> Result<()> {
+    if s.contains('\0') {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "NUL-terminated string contains NUL byte",
+        ));
+    }
+    w.write_all(s.as_bytes())?;
+    w.write_all(&[0])
+}
+
+/// Reads UTF-8 string up to NUL byte, which is consumed but not included
+pub fn read_nul_terminated<R: Read>(r: R) -> Result<String> {
+    from_utf8(read_until_nul(r)?)
+}
+
+/// Writes UTF-8 string into exactly `width` bytes, filling the rest with `pad`
+pub fn write_padded<W: Write>(s: &str, width: usize, pad: u8, mut w: W) -> Result<()> {
+    if s.len() > width {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "string of {} bytes does not fit into {} bytes",
+                s.len(),
+                width
+            ),
+        ));
+    }
+    w.write_all(s.as_bytes())?;
+    for _ in s.len()..width {
+        w.write_all(&[pad])?;
+    }
+    Ok(())
+}
+
+/// Reads UTF-8 string from exactly `width` bytes
+///
+/// Zero padding works like in C: the string ends at the first NUL byte.
+/// Any other `pad` is trimmed from the end of the string.
+pub fn read_padded<R: Read>(mut r: R, width: usize, pad: u8) -> Result<String> {
+    let mut bytes = vec![0; width];
+    r.read_exact(&mut bytes)?;
+
+    let len = if pad == 0 {
+        bytes.iter().position(|&byte| byte == 0).unwrap_or(width)
+    } else {
+        bytes
+            .iter()
+            .rposition(|&byte| byte != pad)
+            .map_or(0, |i| i + 1)
+    };
+    bytes.truncate(len);
+
+    from_utf8(bytes)
+}
+
+fn from_utf16(units: &[u16], surrogates: Surrogates) -> Result<String> {
+    char::decode_utf16(units.iter().copied())
+        .map(|c| match (c, surrogates) {
+            (Ok(c), _) => Ok(c),
+            (Err(_), Surrogates::Replace) => Ok(char::REPLACEMENT_CHARACTER),
+            (Err(err), Surrogates::Reject) => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("string is not valid UTF-16: {}", err),
+            )),
+        })
+        .collect()
+}
+
+/// Writes UTF-16 code units of the string without length or terminator
+pub fn write_utf16<E: Endian, W: Write>(s: &str, mut w: W) -> Result<()> {
+    for unit in s.encode_utf16() {
+        w.try_write::<E, u16>(unit)?;
+    }
+    Ok(())
+}
+
+/// Reads UTF-16 string of exactly `len` code units
+pub fn read_utf16<E: Endian, R: Read>(
+    mut r: R,
+    len: usize,
+    surrogates: Surrogates,
+) -> Result<String> {
+    let units = read_vec(len, || r.try_read::<E, u16>())?;
+    from_utf16(&units, surrogates)
+}
+
+/// Writes UTF-16 string prefixed with its length in code units
+pub fn write_utf16_prefixed<E: Endian, L: Length, W: Write>(s: &str, mut w: W) -> Result<()> {
+    w.write_len::<E, L>(s.encode_utf16().count())?;
+    write_utf16::<E, _>(s, w)
+}
+
+/// Reads UTF-16 string that was written by [`write_utf16_prefixed`]
+pub fn read_utf16_prefixed<E: Endian, L: Length, R: Read>(
+    mut r: R,
+    surrogates: Surrogates,
+) -> Result<String> {
+    let len = r.read_len::<E, L>()?;
+    read_utf16::<E, _>(r, len, surrogates)
+}
+
+/// Writes UTF-16 string followed by 0x0000, fails if the string contains NUL itself
+pub fn write_utf16_nul_terminated<E: Endian, W: Write>(s: &str, mut w: W) -> Result<()> {
+    if s.contains('\0') {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "NUL-terminated string contains NUL character",
+        ));
+    }
+    write_utf16::<E, _>(s, &mut w)?;
+    w.try_write::<E, u16>(0)
+}
+
+/// Reads UTF-16 string up to 0x0000, which is consumed but not included
+pub fn read_utf16_nul_terminated<E: Endian, R: Read>(
+    mut r: R,
+    surrogates: Surrogates,
+) -> Result<String> {
+    let mut units = Vec::new();
+    loop {
+        match r.try_read::<E, u16>()? {
+            0 => return from_utf16(&units, surrogates),
+            unit => units.push(unit),
+        }
+    }
+}
+
+/// C-style string terminated by NUL byte
+impl Io for CString {
+    #[cfg_attr(feature = "inline_io", inline(always))]
+    fn write<W: Write>(&self, mut w: W) -> Result<()> {
+        w.write_all(self.as_bytes_with_nul())
+    }
+
+    #[cfg_attr(feature = "inline_io", inline(always))]
+    fn read<R: Read>(r: R) -> Result<Self> {
+        CString::new(read_until_nul(r)?).map_err(|err| Error::new(ErrorKind::InvalidData, err))
+    }
+}
@@ -0,0 +1,254 @@
+use crate::ext::{EndianReader, EndianWriter};
+use crate::NativeEndian;
+use std::convert::TryFrom;
+use std::io::{Error, ErrorKind, Read, Result, Write};
+
+/// Unsigned integers that can be encoded with variable length
+pub trait Unsigned: Sized + Copy {
+    const BITS: u32;
+
+    fn to_u128(self) -> u128;
+    fn from_u128(value: u128) -> Option<Self>;
+}
+
+/// Signed integers that can be encoded with variable length
+pub trait Signed: Sized + Copy {
+    const BITS: u32;
+
+    fn to_i128(self) -> i128;
+    fn from_i128(value: i128) -> Option<Self>;
+}
+
+macro_rules! impl_integers {
+    ($trait:ident $to:ident $from:ident $wide:ty: $($ty:ty),* $(,)?) => {
+        $(
+            impl $trait for $ty {
+                const BITS: u32 = <$ty>::BITS;
+
+                #[inline]
+                fn $to(self) -> $wide {
+                    self as $wide
+                }
+
+                #[inline]
+                fn $from(value: $wide) -> Option<Self> {
+                    <$ty>::try_from(value).ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_integers!(Unsigned to_u128 from_u128 u128: u8, u16, u32, u64, u128, usize);
+impl_integers!(Signed to_i128 from_i128 i128: i8, i16, i32, i64, i128, isize);
+
+/// Integers which are encoded as LEB128 by `#[io(varint)]`:
+/// unsigned as ULEB128 and signed as SLEB128
+pub trait Leb128: Sized + Copy {
+    fn write_leb128<W: Write>(self, w: W) -> Result<()>;
+    fn read_leb128<R: Read>(r: R) -> Result<Self>;
+}
+
+macro_rules! impl_leb128 {
+    ($write:ident $read:ident: $($ty:ty),* $(,)?) => {
+        $(
+            impl Leb128 for $ty {
+                #[inline]
+                fn write_leb128<W: Write>(self, mut w: W) -> Result<()> {
+                    w.$write(self)
+                }
+
+                #[inline]
+                fn read_leb128<R: Read>(mut r: R) -> Result<Self> {
+                    r.$read()
+                }
+            }
+        )*
+    };
+}
+
+impl_leb128!(write_uleb128 read_uleb128: u8, u16, u32, u64, u128, usize);
+impl_leb128!(write_sleb128 read_sleb128: i8, i16, i32, i64, i128, isize);
+
+/// Maximal number of 7-bit groups needed for an integer of `bits` width
+fn max_len(bits: u32) -> u32 {
+    bits.div_ceil(7)
+}
+
+fn overflow<T>(bits: u32) -> Result<T> {
+    Err(Error::new(
+        ErrorKind::InvalidData,
+        format!("varint does not fit into {} bits", bits),
+    ))
+}
+
+fn too_long<T>(bits: u32) -> Result<T> {
+    Err(Error::new(
+        ErrorKind::InvalidData,
+        format!(
+            "varint of {}-bit integer is longer than {} bytes",
+            bits,
+            max_len(bits)
+        ),
+    ))
+}
+
+fn read_byte<R: Read + ?Sized>(r: &mut R) -> Result<u8> {
+    r.try_read::<NativeEndian, u8>()
+}
+
+fn write_uleb128<W: Write + ?Sized>(w: &mut W, mut value: u128) -> Result<()> {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            return w.try_write::<NativeEndian, u8>(byte);
+        }
+        w.try_write::<NativeEndian, u8>(byte | 0x80)?;
+    }
+}
+
+fn read_uleb128<R: Read + ?Sized>(r: &mut R, bits: u32) -> Result<u128> {
+    let mut value = 0_u128;
+    for i in 0..max_len(bits) {
+        let byte = read_byte(r)?;
+        let group = u128::from(byte & 0x7f);
+        let shift = 7 * i;
+
+        match group.checked_shl(shift) {
+            Some(shifted) if shifted >> shift == group => value |= shifted,
+            _ => return overflow(bits),
+        }
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    too_long(bits)
+}
+
+fn write_sleb128<W: Write + ?Sized>(w: &mut W, mut value: i128) -> Result<()> {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        let sign = byte & 0x40 != 0;
+        if (value == 0 && !sign) || (value == -1 && sign) {
+            return w.try_write::<NativeEndian, u8>(byte);
+        }
+        w.try_write::<NativeEndian, u8>(byte | 0x80)?;
+    }
+}
+
+fn read_sleb128<R: Read + ?Sized>(r: &mut R, bits: u32) -> Result<i128> {
+    let mut value = 0_i128;
+    for i in 0..max_len(bits) {
+        let byte = read_byte(r)?;
+        let group = byte & 0x7f;
+        let shift = 7 * i;
+
+        if shift + 7 > 128 {
+            //only low bits of the last group fit into i128, the rest must repeat the sign
+            let rest = group >> (127 - shift);
+            if rest != 0 && rest != 0x7f >> (127 - shift) {
+                return overflow(bits);
+            }
+        }
+
+        value |= i128::from(group) << shift;
+        if byte & 0x80 == 0 {
+            if shift + 7 < 128 && group & 0x40 != 0 {
+                value |= -1 << (shift + 7);
+            }
+            return Ok(value);
+        }
+    }
+    too_long(bits)
+}
+
+fn write_vlq<W: Write + ?Sized>(w: &mut W, mut value: u128) -> Result<()> {
+    let mut groups = vec![(value & 0x7f) as u8];
+    value >>= 7;
+    while value != 0 {
+        groups.push((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+    groups.reverse();
+    w.write_all(&groups)
+}
+
+fn read_vlq<R: Read + ?Sized>(r: &mut R, bits: u32) -> Result<u128> {
+    let mut value = 0_u128;
+    for _ in 0..max_len(bits) {
+        let byte = read_byte(r)?;
+        if value >> (128 - 7) != 0 {
+            return overflow(bits);
+        }
+
+        value = value << 7 | u128::from(byte & 0x7f);
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    too_long(bits)
+}
+
+/// Allows to write integers with variable length encodings
+pub trait VarintWriter: Write {
+    /// Unsigned LEB128, used by protobuf, WebAssembly and DWARF
+    #[inline]
+    fn write_uleb128<T: Unsigned>(&mut self, value: T) -> Result<()> {
+        write_uleb128(self, value.to_u128())
+    }
+
+    /// Signed LEB128, used by WebAssembly and DWARF
+    #[inline]
+    fn write_sleb128<T: Signed>(&mut self, value: T) -> Result<()> {
+        write_sleb128(self, value.to_i128())
+    }
+
+    /// Signed integer mapped to unsigned one by zigzag, then written as unsigned LEB128
+    #[inline]
+    fn write_zigzag<T: Signed>(&mut self, value: T) -> Result<()> {
+        let value = value.to_i128();
+        write_uleb128(self, ((value << 1) ^ (value >> 127)) as u128)
+    }
+
+    /// Variable-length quantity with the most significant group first, used by MIDI
+    #[inline]
+    fn write_vlq<T: Unsigned>(&mut self, value: T) -> Result<()> {
+        write_vlq(self, value.to_u128())
+    }
+}
+
+impl<W: Write + ?Sized> VarintWriter for W {}
+
+/// Allows to read integers with variable length encodings
+///
+/// Encodings that are longer than needed for `T` are rejected, as well as values out of its range
+pub trait VarintReader: Read {
+    #[inline]
+    fn read_uleb128<T: Unsigned>(&mut self) -> Result<T> {
+        let value = read_uleb128(self, T::BITS)?;
+        T::from_u128(value).map_or_else(|| overflow(T::BITS), Ok)
+    }
+
+    #[inline]
+    fn read_sleb128<T: Signed>(&mut self) -> Result<T> {
+        let value = read_sleb128(self, T::BITS)?;
+        T::from_i128(value).map_or_else(|| overflow(T::BITS), Ok)
+    }
+
+    #[inline]
+    fn read_zigzag<T: Signed>(&mut self) -> Result<T> {
+        let value = read_uleb128(self, T::BITS)?;
+        let value = (value >> 1) as i128 ^ -((value & 1) as i128);
+        T::from_i128(value).map_or_else(|| overflow(T::BITS), Ok)
+    }
+
+    #[inline]
+    fn read_vlq<T: Unsigned>(&mut self) -> Result<T> {
+        let value = read_vlq(self, T::BITS)?;
+        T::from_u128(value).map_or_else(|| overflow(T::BITS), Ok)
+    }
+}
+
+impl<R: Read + ?Sized> VarintReader for R {}
@@ -0,0 +1,61 @@
+use endiannezz::Io;
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct Sample {
+    id: u8,
+    value: u16,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct ParseMe {
+    magic: [u8; 2],
+    #[endian(little)]
+    samples: [u16; 3],
+    matrix: [[i16; 2]; 2],
+    flags: [bool; 2],
+    nested: [Sample; 2],
+}
+
+#[test]
+fn array_struct() {
+    let s1 = ParseMe {
+        magic: [0xca, 0xfe],
+        samples: [1, 2, 3],
+        matrix: [[1, -1], [2, -2]],
+        flags: [true, false],
+        nested: [Sample { id: 1, value: 2 }, Sample { id: 3, value: 4 }],
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    #[rustfmt::skip]
+    assert_eq!(slice, &[
+        0xca, 0xfe,
+        1, 0, 2, 0, 3, 0,
+        0, 1, 0xff, 0xff, 0, 2, 0xff, 0xfe,
+        1, 0,
+        1, 0, 2, 3, 0, 4,
+    ]);
+
+    let s2 = ParseMe::read(&mut slice).unwrap();
+    assert_eq!(s1, s2);
+
+    let mut short: &[u8] = &[0xca, 0xfe, 1, 0];
+    assert!(ParseMe::read(&mut short).is_err());
+}
+
+#[test]
+fn array_io() {
+    let a1 = [Sample { id: 5, value: 6 }, Sample { id: 7, value: 8 }];
+
+    let mut vec = Vec::new();
+    a1.write(&mut vec).unwrap();
+    assert_eq!(vec, &[5, 0, 6, 7, 0, 8]);
+
+    let a2 = <[Sample; 2]>::read(vec.as_slice()).unwrap();
+    assert_eq!(a1, a2);
+}
@@ -0,0 +1,75 @@
+use endiannezz::Io;
+use std::io::ErrorKind;
+
+const LIMIT: u32 = 1 << 16;
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+#[io(assert = width * height < LIMIT, "image is too large")]
+struct Image {
+    #[io(assert = *version <= 3, "unsupported version")]
+    version: u8,
+    #[io(assert = *width > 0)]
+    width: u32,
+    height: u32,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(little)]
+#[io(assert_on_write)]
+#[io(assert = *min <= *max)]
+struct Range {
+    min: u16,
+    max: u16,
+}
+
+#[test]
+fn assert_struct() {
+    let s1 = Image {
+        version: 1,
+        width: 2,
+        height: 3,
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    assert_eq!(slice, &[1, 0, 0, 0, 2, 0, 0, 0, 3]);
+
+    let s2 = Image::read(&mut slice).unwrap();
+    assert_eq!(s1, s2);
+
+    let check = |bytes: &[u8], message: &str| {
+        let err = Image::read(bytes).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), message);
+    };
+    check(&[4, 0, 0, 0, 2, 0, 0, 0, 3], "unsupported version");
+    check(&[1, 0, 0, 0, 0, 0, 0, 0, 3], "assertion failed: * width > 0");
+    check(&[1, 0, 0, 2, 0, 0, 0, 1, 0], "image is too large");
+
+    //assertions are not checked on write by default
+    let invalid = Image {
+        version: 4,
+        ..s1
+    };
+    invalid.write(Vec::new()).unwrap();
+}
+
+#[test]
+fn assert_on_write() {
+    let s1 = Range { min: 1, max: 2 };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+    assert_eq!(vec, &[1, 0, 2, 0]);
+
+    let s2 = Range::read(vec.as_slice()).unwrap();
+    assert_eq!(s1, s2);
+
+    let mut vec = Vec::new();
+    let err = Range { min: 2, max: 1 }.write(&mut vec).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert!(vec.is_empty());
+}
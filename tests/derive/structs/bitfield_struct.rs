@@ -0,0 +1,101 @@
+use endiannezz::Io;
+use std::io::ErrorKind;
+
+#[derive(Io, Debug, PartialEq, Copy, Clone)]
+#[endian(big)]
+#[repr(u8)]
+enum Ecn {
+    NotEct = 0,
+    Ect1 = 1,
+    Ect0 = 2,
+    Ce = 3,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+#[io(bitfield = u16)]
+struct ParseMe {
+    #[bits(4)]
+    version: u8,
+    #[bits(4)]
+    ihl: u8,
+    #[bits(6)]
+    dscp: u8,
+    #[bits(2)]
+    ecn: Ecn,
+    total_length: u16,
+    #[bits(1)]
+    reserved: bool,
+    #[bits(1)]
+    dont_fragment: bool,
+    #[bits(1)]
+    more_fragments: bool,
+    #[bits(13)]
+    offset: u16,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(little)]
+#[io(bitfield = u8, bit_order = lsb)]
+struct Lsb(#[bits(3)] i8, #[bits(5)] u8);
+
+#[test]
+fn bitfield_struct() {
+    let s1 = ParseMe {
+        version: 4,
+        ihl: 5,
+        dscp: 46,
+        ecn: Ecn::Ect0,
+        total_length: 1500,
+        reserved: false,
+        dont_fragment: true,
+        more_fragments: false,
+        offset: 0x123,
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    assert_eq!(slice, &[0x45, 0xba, 0x05, 0xdc, 0x41, 0x23]);
+
+    let s2 = ParseMe::read(&mut slice).unwrap();
+    assert_eq!(s1, s2);
+}
+
+#[test]
+fn bitfield_lsb() {
+    let s1 = Lsb(-2, 21);
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    //5 bits of 21 follow 3 bits of -2 starting from the least significant bit
+    assert_eq!(slice, &[0b1010_1110]);
+
+    let s2 = Lsb::read(&mut slice).unwrap();
+    assert_eq!(s1, s2);
+}
+
+#[test]
+fn bitfield_out_of_range() {
+    let s = ParseMe {
+        version: 16,
+        ihl: 5,
+        dscp: 0,
+        ecn: Ecn::NotEct,
+        total_length: 0,
+        reserved: false,
+        dont_fragment: false,
+        more_fragments: false,
+        offset: 0,
+    };
+
+    let err = s.write(&mut Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert_eq!(err.to_string(), "`version` does not fit into 4 bits");
+
+    let err = Lsb(4, 0).write(&mut Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
@@ -0,0 +1,50 @@
+use endiannezz::Io;
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct Packet {
+    #[io(calc = self.items.len() as u16)]
+    num_items: u16,
+    #[io(calc = name.len() as u8)]
+    name_len: u8,
+    #[io(count = num_items)]
+    items: Vec<u32>,
+    #[io(fixed = 8)]
+    name: String,
+    #[io(calc = items.iter().sum(), varint)]
+    total: u32,
+}
+
+#[test]
+fn calc_struct() {
+    let s1 = Packet {
+        num_items: 0,
+        name_len: 0,
+        items: vec![1, 2],
+        name: String::from("abc"),
+        total: 0,
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    #[rustfmt::skip]
+    assert_eq!(slice, &[
+        0, 2, 3,
+        0, 0, 0, 1, 0, 0, 0, 2,
+        b'a', b'b', b'c', 0, 0, 0, 0, 0,
+        3,
+    ]);
+
+    let s2 = Packet::read(&mut slice).unwrap();
+    assert_eq!(
+        s2,
+        Packet {
+            num_items: 2,
+            name_len: 3,
+            total: 3,
+            ..s1
+        }
+    );
+}
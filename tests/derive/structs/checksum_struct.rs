@@ -0,0 +1,117 @@
+use endiannezz::checksum::Checksum;
+use endiannezz::Io;
+use std::io::ErrorKind;
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct Chunk {
+    length: u32,
+    kind: [u8; 4],
+    #[io(count = *length as usize)]
+    data: Vec<u8>,
+    #[io(checksum = crc32, over = "kind..=data")]
+    crc: u32,
+}
+
+/// Checksum that is not built into the crate
+#[derive(Default)]
+struct Xor(u8);
+
+impl Checksum for Xor {
+    type Output = u8;
+
+    fn update(&mut self, bytes: &[u8]) {
+        self.0 = bytes.iter().fold(self.0, |acc, byte| acc ^ byte);
+    }
+
+    fn finish(&self) -> u8 {
+        self.0
+    }
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(little)]
+#[io(bitfield = u8)]
+struct Record {
+    #[bits(4)]
+    kind: u8,
+    #[bits(4)]
+    flags: u8,
+    #[io(align = 4)]
+    value: u16,
+    #[io(checksum = sum8)]
+    sum: u8,
+    #[io(checksum = Xor, over = "value..")]
+    xor: u8,
+    #[io(checksum = adler32, over = "..sum")]
+    adler: u32,
+}
+
+#[test]
+fn checksum_struct() {
+    let s1 = Chunk {
+        length: 0,
+        kind: *b"IEND",
+        data: vec![],
+        crc: 0,
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    assert_eq!(slice, b"\0\0\0\0IEND\xae\x42\x60\x82");
+
+    let s2 = Chunk::read(&mut slice).unwrap();
+    assert_eq!(
+        s2,
+        Chunk {
+            crc: 0xae426082,
+            ..s1
+        }
+    );
+
+    let corrupted: &[u8] = b"\0\0\0\0IENT\xae\x42\x60\x82";
+    let err = Chunk::read(corrupted).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        err.to_string(),
+        "checksum mismatch of `crc`, stored 0xae426082, computed 0xb3f570e6"
+    );
+}
+
+#[test]
+fn checksum_custom() {
+    let s1 = Record {
+        kind: 1,
+        flags: 2,
+        value: 0x0304,
+        sum: 0,
+        xor: 0,
+        adler: 0,
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    #[rustfmt::skip]
+    assert_eq!(slice, &[
+        0x12, 0, 0, 0,
+        0x04, 0x03,
+        0x19,
+        0x1e,
+        0x1a, 0, 0x7d, 0,
+    ]);
+
+    let s2 = Record::read(&mut slice).unwrap();
+    assert_eq!(
+        s2,
+        Record {
+            sum: 0x19,
+            xor: 0x1e,
+            adler: 0x7d001a,
+            ..s1
+        }
+    );
+}
@@ -0,0 +1,88 @@
+use endiannezz::Io;
+use std::io::ErrorKind;
+
+const HAS_CRC: u8 = 0x01;
+const HAS_NAME: u8 = 0x02;
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct Frame {
+    flags: u8,
+    #[io(if = flags & HAS_CRC != 0)]
+    crc: Option<u32>,
+    #[io(if = flags & HAS_NAME != 0)]
+    name: Option<(u8, u8)>,
+    payload: u16,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(little)]
+struct Versioned {
+    version: u8,
+    #[io(if = *version >= 2)]
+    #[endian(big)]
+    extra: Option<u16>,
+}
+
+#[test]
+fn conditional_struct() {
+    let s1 = Frame {
+        flags: HAS_CRC,
+        crc: Some(0xdeadbeef),
+        name: None,
+        payload: 1,
+    };
+    let s2 = Frame {
+        flags: HAS_CRC | HAS_NAME,
+        crc: Some(2),
+        name: Some((3, 4)),
+        payload: 5,
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+    s2.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    #[rustfmt::skip]
+    assert_eq!(slice, &[
+        1, 0xde, 0xad, 0xbe, 0xef, 0, 1,
+        3, 0, 0, 0, 2, 3, 4, 0, 5,
+    ]);
+
+    assert_eq!(Frame::read(&mut slice).unwrap(), s1);
+    assert_eq!(Frame::read(&mut slice).unwrap(), s2);
+
+    let mismatch = Frame { crc: None, ..s1 };
+    let err = mismatch.write(Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn conditional_version() {
+    let s1 = Versioned {
+        version: 1,
+        extra: None,
+    };
+    let s2 = Versioned {
+        version: 2,
+        extra: Some(3),
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+    s2.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    assert_eq!(slice, &[1, 2, 0, 3]);
+
+    assert_eq!(Versioned::read(&mut slice).unwrap(), s1);
+    assert_eq!(Versioned::read(&mut slice).unwrap(), s2);
+
+    let mismatch = Versioned {
+        version: 2,
+        extra: None,
+    };
+    let err = mismatch.write(Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
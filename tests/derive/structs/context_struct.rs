@@ -0,0 +1,179 @@
+use endiannezz::{Io, IoWith};
+use std::io::ErrorKind;
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+#[io(context(version: u8, size: u16))]
+struct Record {
+    id: u16,
+    #[io(count = size)]
+    data: Vec<u8>,
+    #[io(count = if version > 1 { 1 } else { 0 })]
+    flags: Vec<u32>,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct File {
+    version: u8,
+    size: u16,
+    #[io(args(*version, *size))]
+    first: Record,
+    #[io(count = 2, args(*version, 1))]
+    rest: Vec<Record>,
+}
+
+#[test]
+fn context_struct() {
+    let s1 = Record {
+        id: 1,
+        data: vec![2, 3],
+        flags: vec![4],
+    };
+
+    let mut vec = Vec::new();
+    s1.write_with(&mut vec, (2, 2)).unwrap();
+
+    let mut slice = vec.as_slice();
+    assert_eq!(slice, &[0, 1, 2, 3, 0, 0, 0, 4]);
+
+    let s2 = Record::read_with(&mut slice, (2, 2)).unwrap();
+    assert_eq!(s1, s2);
+
+    let err = s1.write_with(Vec::new(), (1, 2)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn context_args() {
+    let s1 = File {
+        version: 1,
+        size: 3,
+        first: Record {
+            id: 1,
+            data: vec![2, 3, 4],
+            flags: vec![],
+        },
+        rest: vec![
+            Record {
+                id: 5,
+                data: vec![6],
+                flags: vec![],
+            },
+            Record {
+                id: 7,
+                data: vec![8],
+                flags: vec![],
+            },
+        ],
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    #[rustfmt::skip]
+    assert_eq!(slice, &[
+        1, 0, 3,
+        0, 1, 2, 3, 4,
+        0, 5, 6,
+        0, 7, 8,
+    ]);
+
+    let s2 = File::read(&mut slice).unwrap();
+    assert_eq!(s1, s2);
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+#[io(context(size: u16))]
+struct Child {
+    #[io(count = size)]
+    data: Vec<u8>,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct Parent {
+    len: u16,
+    item: u16,
+    #[io(len = u8, args(*len))]
+    children: Vec<Child>,
+    #[io(count = 1, args(*item))]
+    counted: Vec<Child>,
+    #[io(args(*item))]
+    pair: [Child; 1],
+}
+
+#[test]
+fn context_args_named_like_locals() {
+    let s1 = Parent {
+        len: 1,
+        item: 2,
+        children: vec![Child { data: vec![9] }, Child { data: vec![8] }],
+        counted: vec![Child { data: vec![7, 6] }],
+        pair: [Child { data: vec![5, 4] }],
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    #[rustfmt::skip]
+    assert_eq!(slice, &[
+        0, 1, 0, 2,
+        2, 9, 8,
+        7, 6,
+        5, 4,
+    ]);
+
+    let s2 = Parent::read(&mut slice).unwrap();
+    assert_eq!(s1, s2);
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+#[io(context(size: u16))]
+struct Sample {
+    id: u16,
+    #[io(count = size)]
+    data: Vec<u16>,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct Mixed {
+    size: u16,
+    #[endian(little)]
+    #[io(args(*size))]
+    little: Sample,
+    #[io(args(*size))]
+    big: Sample,
+}
+
+#[test]
+fn context_args_with_endian() {
+    let sample = || Sample {
+        id: 1,
+        data: vec![2, 3],
+    };
+    let s1 = Mixed {
+        size: 2,
+        little: sample(),
+        big: sample(),
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    #[rustfmt::skip]
+    assert_eq!(slice, &[
+        0, 2,
+        1, 0, 2, 0, 3, 0,
+        0, 1, 0, 2, 0, 3,
+    ]);
+
+    let s2 = Mixed::read(&mut slice).unwrap();
+    assert_eq!(s1, s2);
+}
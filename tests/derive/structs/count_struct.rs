@@ -0,0 +1,82 @@
+use endiannezz::Io;
+use std::io::ErrorKind;
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct ParseMe {
+    num_entries: u16,
+    num_pairs: u8,
+    version: u8,
+    #[io(count = num_entries)]
+    entries: Vec<u16>,
+    #[io(count = *num_pairs as usize * 2)]
+    pairs: Vec<u8>,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(little)]
+struct Tuple(u8, #[io(count = field_0)] Vec<i16>);
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(little)]
+struct Colors {
+    r: u8,
+    w: u8,
+    #[io(count = r)]
+    item: Vec<u8>,
+}
+
+#[test]
+fn count_struct() {
+    let s1 = ParseMe {
+        num_entries: 2,
+        num_pairs: 1,
+        version: 3,
+        entries: vec![0x10, 0x20],
+        pairs: vec![4, 5],
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    assert_eq!(slice, &[0, 2, 1, 3, 0, 0x10, 0, 0x20, 4, 5]);
+
+    let s2 = ParseMe::read(&mut slice).unwrap();
+    assert_eq!(s1, s2);
+
+    let mismatch = ParseMe {
+        num_entries: 3,
+        ..s2
+    };
+    let err = mismatch.write(Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn count_tuple_struct() {
+    let s1 = Tuple(2, vec![-1, 1]);
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+    assert_eq!(vec, &[2, 0xff, 0xff, 1, 0]);
+
+    let s2 = Tuple::read(vec.as_slice()).unwrap();
+    assert_eq!(s1, s2);
+}
+
+#[test]
+fn count_reserved_names() {
+    let s1 = Colors {
+        r: 1,
+        w: 2,
+        item: vec![3],
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+    assert_eq!(vec, &[1, 2, 3]);
+
+    let s2 = Colors::read(vec.as_slice()).unwrap();
+    assert_eq!(s1, s2);
+}
@@ -0,0 +1,116 @@
+use endiannezz::ext::{EndianReader, EndianWriter};
+use endiannezz::{BigEndian, Endian, Io, IoEndian, LittleEndian};
+use std::io::{Read, Result, Write};
+
+#[derive(Debug, PartialEq)]
+struct Length(u32);
+
+impl Io for Length {
+    fn write<W: Write>(&self, w: W) -> Result<()> {
+        self.write_endian::<LittleEndian, _>(w)
+    }
+
+    fn read<R: Read>(r: R) -> Result<Self> {
+        Self::read_endian::<LittleEndian, _>(r)
+    }
+}
+
+impl IoEndian for Length {
+    fn write_endian<E: Endian, W: Write>(&self, mut w: W) -> Result<()> {
+        w.try_write::<E, u32>(self.0)
+    }
+
+    fn read_endian<E: Endian, R: Read>(mut r: R) -> Result<Self> {
+        Ok(Self(r.try_read::<E, u32>()?))
+    }
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(little)]
+struct Point {
+    x: u16,
+    #[endian(big)]
+    y: u16,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(little)]
+struct Shape {
+    points: [Point; 2],
+    length: Length,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(little)]
+struct ParseMe {
+    native: Shape,
+    #[endian(big)]
+    swapped: Shape,
+    #[endian(big)]
+    pair: (Point, Option<Length>),
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct Forced {
+    native: Point,
+    //matches the default, but still overrides the own byte order of `Point`
+    #[endian(big)]
+    forced: Point,
+}
+
+#[test]
+fn endian_struct() {
+    let shape = || Shape {
+        points: [Point { x: 1, y: 2 }, Point { x: 3, y: 4 }],
+        length: Length(5),
+    };
+
+    let s1 = ParseMe {
+        native: shape(),
+        swapped: shape(),
+        pair: (Point { x: 6, y: 7 }, Some(Length(8))),
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    #[rustfmt::skip]
+    assert_eq!(slice, &[
+        1, 0, 0, 2, 3, 0, 0, 4, 5, 0, 0, 0,
+        //`y` keeps its own `#[endian(big)]` in both cases
+        0, 1, 0, 2, 0, 3, 0, 4, 0, 0, 0, 5,
+        0, 6, 0, 7, 1, 0, 0, 0, 8,
+    ]);
+
+    let s2 = ParseMe::read(&mut slice).unwrap();
+    assert_eq!(s1, s2);
+}
+
+#[test]
+fn endian_struct_forced_default() {
+    let f1 = Forced {
+        native: Point { x: 1, y: 2 },
+        forced: Point { x: 3, y: 4 },
+    };
+
+    let mut vec = Vec::new();
+    f1.write(&mut vec).unwrap();
+    assert_eq!(vec, &[1, 0, 0, 2, 0, 3, 0, 4]);
+
+    let f2 = Forced::read(vec.as_slice()).unwrap();
+    assert_eq!(f1, f2);
+}
+
+#[test]
+fn endian_io() {
+    let p1 = Point { x: 1, y: 2 };
+
+    let mut vec = Vec::new();
+    p1.write_endian::<BigEndian, _>(&mut vec).unwrap();
+    assert_eq!(vec, &[0, 1, 0, 2]);
+
+    let p2 = Point::read_endian::<BigEndian, _>(vec.as_slice()).unwrap();
+    assert_eq!(p1, p2);
+}
@@ -0,0 +1,119 @@
+use endiannezz::{Io, IoSeek};
+use std::io::{Cursor, ErrorKind};
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(little)]
+struct Entry {
+    #[io(cstr)]
+    name: String,
+    #[io(len = u8)]
+    value: String,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(little)]
+struct Record {
+    #[io(len_of = entry)]
+    #[endian(big)]
+    len: u16,
+    kind: u8,
+    #[io(size = len)]
+    entry: Entry,
+    #[io(len_of = tail)]
+    tail_len: u8,
+    #[io(cstr)]
+    tail: String,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct Chunk {
+    tag: u8,
+    #[io(len_of = data)]
+    len: u16,
+    #[io(align = 4, len = u8)]
+    data: String,
+    #[io(align = 4)]
+    end: u8,
+    #[io(checksum = crc32, over = "end..=end")]
+    crc: u32,
+}
+
+#[test]
+fn len_of_struct() {
+    let s1 = Record {
+        len: 0,
+        kind: 7,
+        entry: Entry {
+            name: "ab".to_string(),
+            value: "xyz".to_string(),
+        },
+        tail_len: 0,
+        tail: "!".to_string(),
+    };
+
+    #[rustfmt::skip]
+    let expected = [
+        0, 7, 7,
+        b'a', b'b', 0, 3, b'x', b'y', b'z',
+        2, b'!', 0,
+    ];
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+    assert_eq!(vec, expected);
+
+    //the writer ends up after the record, so the following data isn't overwritten
+    let mut cursor = Cursor::new(Vec::new());
+    s1.write_seek(&mut cursor).unwrap();
+    std::io::Write::write_all(&mut cursor, &[0xff]).unwrap();
+    let vec = cursor.into_inner();
+    assert_eq!(vec[..expected.len()], expected);
+    assert_eq!(vec[expected.len()..], [0xff]);
+
+    let s2 = Record::read(&mut &expected[..]).unwrap();
+    assert_eq!(
+        s2,
+        Record {
+            len: 7,
+            tail_len: 2,
+            ..s1
+        }
+    );
+
+    let s3 = Record {
+        tail: "a".repeat(300),
+        ..s2
+    };
+    let err = s3.write(&mut Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert_eq!(err.to_string(), "length of `tail` is too large: 301");
+    let err = s3.write_seek(&mut Cursor::new(Vec::new())).unwrap_err();
+    assert_eq!(err.to_string(), "length of `tail` is too large: 301");
+}
+
+#[test]
+fn len_of_seek_struct() {
+    let s1 = Chunk {
+        tag: 1,
+        len: 0,
+        data: "abcde".to_string(),
+        end: 2,
+        crc: 0,
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+    #[rustfmt::skip]
+    assert_eq!(vec[..13], [
+        1, 0, 6, 0,
+        5, b'a', b'b', b'c', b'd', b'e', 0, 0,
+        2,
+    ]);
+
+    //aligned fields are still counted from the start of the type after patching
+    let mut cursor = Cursor::new(vec![0xff; 3]);
+    cursor.set_position(3);
+    s1.write_seek(&mut cursor).unwrap();
+    assert_eq!(cursor.into_inner()[3..], vec[..]);
+}
@@ -0,0 +1,102 @@
+use endiannezz::{Endianness, Io};
+use std::io::ErrorKind;
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+#[io(magic = b"\x89PNG\r\n\x1a\n")]
+struct Png {
+    width: u32,
+    height: u32,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+#[io(magic = 0xCAFEBABE_u32)]
+struct Class {
+    minor: u16,
+    major: u16,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(runtime)]
+#[io(magic = 0x1234_u16)]
+struct Runtime {
+    value: u16,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(little)]
+#[io(magic = b"OP")]
+#[repr(u8)]
+enum Op {
+    Nop = 0,
+    Push(u16) = 1,
+}
+
+#[test]
+fn magic_struct() {
+    let s1 = Png {
+        width: 1,
+        height: 2,
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    assert_eq!(slice, b"\x89PNG\r\n\x1a\n\0\0\0\x01\0\0\0\x02");
+
+    let s2 = Png::read(&mut slice).unwrap();
+    assert_eq!(s1, s2);
+
+    let mut garbage: &[u8] = b"\x89PNX\r\n\x1a\n\0\0\0\x01\0\0\0\x02";
+    let err = Png::read(&mut garbage).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        err.to_string(),
+        "invalid magic, expected [89, 50, 4e, 47, 0d, 0a, 1a, 0a], \
+         found [89, 50, 4e, 58, 0d, 0a, 1a, 0a]",
+    );
+}
+
+#[test]
+fn magic_integer() {
+    let s1 = Class {
+        minor: 0,
+        major: 52,
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    assert_eq!(slice, &[0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 52]);
+
+    let s2 = Class::read(&mut slice).unwrap();
+    assert_eq!(s1, s2);
+
+    let s3 = Runtime { value: 1 };
+
+    let mut vec = Vec::new();
+    Endianness::Little.write_io(&s3, &mut vec).unwrap();
+    Endianness::Big.write_io(&s3, &mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    assert_eq!(slice, &[0x34, 0x12, 1, 0, 0x12, 0x34, 0, 1]);
+
+    assert_eq!(Endianness::Little.read_io::<Runtime, _>(&mut slice).unwrap(), s3);
+    assert_eq!(Endianness::Big.read_io::<Runtime, _>(&mut slice).unwrap(), s3);
+}
+
+#[test]
+fn magic_enum() {
+    let mut vec = Vec::new();
+    Op::Nop.write(&mut vec).unwrap();
+    Op::Push(3).write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    assert_eq!(slice, b"OP\0OP\x01\x03\0");
+
+    assert_eq!(Op::read(&mut slice).unwrap(), Op::Nop);
+    assert_eq!(Op::read(&mut slice).unwrap(), Op::Push(3));
+}
@@ -0,0 +1,74 @@
+use endiannezz::Io;
+use std::convert::TryFrom;
+use std::io::ErrorKind;
+use std::time::Duration;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Kind {
+    Text,
+    Binary,
+}
+
+impl TryFrom<u8> for Kind {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Kind::Text),
+            2 => Ok(Kind::Binary),
+            _ => Err(format!("unknown kind {}", value)),
+        }
+    }
+}
+
+fn fixed_to_f32(value: i16) -> f32 {
+    f32::from(value) / 256.0
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct Message {
+    #[io(wire = u32, map = (|secs| Duration::from_secs(u64::from(secs)), |d: &Duration| d.as_secs() as u32))]
+    timeout: Duration,
+    #[io(wire = u8, try_map = (Kind::try_from, |kind: &Kind| Ok::<_, String>(*kind as u8 + 1)))]
+    kind: Kind,
+    #[io(wire = i16, map = (fixed_to_f32, |value: &f32| (value * 256.0) as i16))]
+    #[endian(little)]
+    scale: f32,
+    #[io(wire = [u8; 2], try_map = (
+        |bytes: [u8; 2]| String::from_utf8(bytes.to_vec()),
+        |code: &String| <[u8; 2]>::try_from(code.as_bytes()),
+    ))]
+    code: String,
+}
+
+#[test]
+fn map_struct() {
+    let s1 = Message {
+        timeout: Duration::from_secs(30),
+        kind: Kind::Binary,
+        scale: 1.5,
+        code: String::from("ok"),
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    assert_eq!(slice, &[0, 0, 0, 30, 2, 0x80, 0x01, b'o', b'k']);
+
+    let s2 = Message::read(&mut slice).unwrap();
+    assert_eq!(s1, s2);
+
+    let garbage: &[u8] = &[0, 0, 0, 30, 3, 0x80, 0x01, b'o', b'k'];
+    let err = Message::read(garbage).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(err.to_string(), "cannot convert `kind`: unknown kind 3");
+
+    let invalid = Message {
+        code: String::from("long"),
+        ..s1
+    };
+    let err = invalid.write(Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
@@ -0,0 +1,148 @@
+use endiannezz::{ByteOrderMark, Endianness, Io};
+use std::io::{ErrorKind, Result};
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct Entry {
+    tag: u16,
+    count: u32,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(from = match order {
+    b"II" => Some(Endianness::Little),
+    b"MM" => Some(Endianness::Big),
+    _ => None,
+})]
+struct Tiff {
+    order: [u8; 2],
+    magic: u16,
+    entry: Entry,
+    #[endian(big)]
+    fixed: u16,
+}
+
+#[derive(Io, Debug, Clone, Copy, PartialEq)]
+#[endian(big)]
+struct Class(u8);
+
+impl ByteOrderMark for Class {
+    fn endianness(&self) -> Result<Endianness> {
+        match self.0 {
+            1 => Ok(Endianness::Little),
+            2 => Ok(Endianness::Big),
+            _ => Err(ErrorKind::InvalidData.into()),
+        }
+    }
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(from = data)]
+struct Elf {
+    #[endian(little)]
+    magic: u32,
+    data: Class,
+    version: u32,
+}
+
+#[test]
+fn marked_struct() {
+    let s1 = Tiff {
+        order: *b"II",
+        magic: 42,
+        entry: Entry { tag: 1, count: 2 },
+        fixed: 3,
+    };
+    let s2 = Tiff {
+        order: *b"MM",
+        magic: 42,
+        entry: Entry { tag: 1, count: 2 },
+        fixed: 3,
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+    s2.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    #[rustfmt::skip]
+    assert_eq!(slice, &[
+        b'I', b'I', 42, 0, 1, 0, 2, 0, 0, 0, 0, 3,
+        b'M', b'M', 0, 42, 0, 1, 0, 0, 0, 2, 0, 3,
+    ]);
+
+    assert_eq!(Tiff::read(&mut slice).unwrap(), s1);
+    assert_eq!(Tiff::read(&mut slice).unwrap(), s2);
+
+    let mut garbage: &[u8] = b"XX\0\0";
+    let err = Tiff::read(&mut garbage).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn marked_struct_custom() {
+    let s1 = Elf {
+        magic: 0x464c457f,
+        data: Class(2),
+        version: 1,
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    assert_eq!(slice, b"\x7fELF\x02\0\0\0\x01");
+
+    let s2 = Elf::read(&mut slice).unwrap();
+    assert_eq!(s1, s2);
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct Header {
+    order: Class,
+    version: u8,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(from = header.order)]
+struct Section {
+    header: Header,
+    count: u16,
+    //named like the member of the mark, but it's not mentioned by it
+    order: u8,
+}
+
+#[test]
+fn marked_struct_member() {
+    let s1 = Section {
+        header: Header {
+            order: Class(2),
+            version: 1,
+        },
+        count: 3,
+        order: 4,
+    };
+    let s2 = Section {
+        header: Header {
+            order: Class(1),
+            version: 1,
+        },
+        count: 3,
+        order: 4,
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+    s2.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    #[rustfmt::skip]
+    assert_eq!(slice, &[
+        2, 1, 0, 3, 4,
+        1, 1, 3, 0, 4,
+    ]);
+
+    assert_eq!(Section::read(&mut slice).unwrap(), s1);
+    assert_eq!(Section::read(&mut slice).unwrap(), s2);
+}
@@ -0,0 +1,88 @@
+use endiannezz::Io;
+use std::io::ErrorKind;
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct Inner {
+    value: u16,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct ParseMe {
+    flagged: Option<u16>,
+    #[endian(little)]
+    nested: Option<Inner>,
+    #[io(sentinel = 0xffff)]
+    index: Option<u16>,
+    #[endian(little)]
+    #[io(sentinel = -1)]
+    offset: Option<i32>,
+}
+
+#[test]
+fn option_struct() {
+    let s1 = ParseMe {
+        flagged: Some(1),
+        nested: None,
+        index: Some(2),
+        offset: None,
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    assert_eq!(slice, &[1, 0, 1, 0, 0, 2, 0xff, 0xff, 0xff, 0xff]);
+
+    let s2 = ParseMe::read(&mut slice).unwrap();
+    assert_eq!(s1, s2);
+
+    let s3 = ParseMe {
+        flagged: None,
+        nested: Some(Inner { value: 3 }),
+        index: None,
+        offset: Some(4),
+    };
+
+    let mut vec = Vec::new();
+    s3.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    //`#[endian(little)]` of the field is passed to `Inner`
+    assert_eq!(slice, &[0, 1, 3, 0, 0xff, 0xff, 4, 0, 0, 0]);
+
+    let s4 = ParseMe::read(&mut slice).unwrap();
+    assert_eq!(s3, s4);
+}
+
+#[test]
+fn option_struct_reserved() {
+    let s1 = ParseMe {
+        flagged: None,
+        nested: None,
+        index: Some(0xffff),
+        offset: None,
+    };
+
+    let err = s1.write(Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn option_io() {
+    let o1 = Some(Inner { value: 5 });
+
+    let mut vec = Vec::new();
+    o1.write(&mut vec).unwrap();
+    assert_eq!(vec, &[1, 0, 5]);
+
+    let o2 = Option::<Inner>::read(vec.as_slice()).unwrap();
+    assert_eq!(o1, o2);
+
+    #[cfg(not(feature = "unchecked_bool"))]
+    {
+        let mut garbage: &[u8] = &[2, 0, 5];
+        assert!(Option::<Inner>::read(&mut garbage).is_err());
+    }
+}
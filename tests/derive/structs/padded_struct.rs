@@ -0,0 +1,96 @@
+use endiannezz::Io;
+use std::io::ErrorKind;
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(little)]
+struct Header {
+    kind: u8,
+    #[io(align = 4)]
+    size: u32,
+    #[io(pad_before = 2, pad_after = 1)]
+    flags: u8,
+    #[io(align = 8)]
+    offset: u64,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+#[io(strict_padding)]
+struct Strict {
+    len: u8,
+    #[io(pad_after = *len as usize)]
+    value: u16,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(little)]
+#[io(magic = b"AB")]
+struct Magic {
+    #[io(align = 4)]
+    value: u16,
+}
+
+#[test]
+fn padded_struct() {
+    let s1 = Header {
+        kind: 1,
+        size: 2,
+        flags: 3,
+        offset: 4,
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    #[rustfmt::skip]
+    assert_eq!(slice, &[
+        1, 0, 0, 0,
+        2, 0, 0, 0,
+        0, 0, 3, 0,
+        0, 0, 0, 0,
+        4, 0, 0, 0, 0, 0, 0, 0,
+    ]);
+
+    let s2 = Header::read(&mut slice).unwrap();
+    assert_eq!(s1, s2);
+
+    //padding is skipped unless strict mode is enabled
+    #[rustfmt::skip]
+    let garbage: &[u8] = &[
+        1, 9, 9, 9,
+        2, 0, 0, 0,
+        9, 9, 3, 9,
+        9, 9, 9, 9,
+        4, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    assert_eq!(Header::read(garbage).unwrap(), s1);
+}
+
+#[test]
+fn padded_strict() {
+    let s1 = Strict { len: 2, value: 3 };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+    assert_eq!(vec, &[2, 0, 3, 0, 0]);
+
+    let s2 = Strict::read(vec.as_slice()).unwrap();
+    assert_eq!(s1, s2);
+
+    let garbage: &[u8] = &[2, 0, 3, 0, 1];
+    let err = Strict::read(garbage).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn padded_after_magic() {
+    let s1 = Magic { value: 1 };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+    assert_eq!(vec, b"AB\0\0\x01\0");
+
+    let s2 = Magic::read(vec.as_slice()).unwrap();
+    assert_eq!(s1, s2);
+}
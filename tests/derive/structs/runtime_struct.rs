@@ -0,0 +1,87 @@
+use endiannezz::{Endianness, Io};
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct Entry {
+    tag: u16,
+    value: u32,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(runtime)]
+struct ParseMe {
+    version: u16,
+    #[endian(little)]
+    flags: u16,
+    entries: [Entry; 2],
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct Container {
+    #[endian(little)]
+    inner: ParseMe,
+    tail: u16,
+}
+
+#[test]
+fn runtime_struct() {
+    let s1 = ParseMe {
+        version: 42,
+        flags: 1,
+        entries: [Entry { tag: 1, value: 2 }, Entry { tag: 3, value: 4 }],
+    };
+
+    let mut vec = Vec::new();
+    Endianness::Little.write_io(&s1, &mut vec).unwrap();
+    Endianness::Big.write_io(&s1, &mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    #[rustfmt::skip]
+    assert_eq!(slice, &[
+        42, 0, 1, 0, 1, 0, 2, 0, 0, 0, 3, 0, 4, 0, 0, 0,
+        0, 42, 1, 0, 0, 1, 0, 0, 0, 2, 0, 3, 0, 0, 0, 4,
+    ]);
+
+    let s2: ParseMe = Endianness::Little.read_io(&mut slice).unwrap();
+    let s3: ParseMe = Endianness::Big.read_io(&mut slice).unwrap();
+    assert_eq!(s1, s2);
+    assert_eq!(s1, s3);
+}
+
+#[test]
+fn runtime_struct_nested() {
+    let c1 = Container {
+        inner: ParseMe {
+            version: 42,
+            flags: 1,
+            entries: [Entry { tag: 1, value: 2 }, Entry { tag: 3, value: 4 }],
+        },
+        tail: 5,
+    };
+
+    let mut vec = Vec::new();
+    c1.write(&mut vec).unwrap();
+    #[rustfmt::skip]
+    assert_eq!(vec, &[
+        42, 0, 1, 0, 1, 0, 2, 0, 0, 0, 3, 0, 4, 0, 0, 0,
+        0, 5,
+    ]);
+
+    let c2 = Container::read(vec.as_slice()).unwrap();
+    assert_eq!(c1, c2);
+}
+
+#[test]
+fn endianness() {
+    let mut vec = Vec::new();
+    Endianness::Big.write(0x1234_u16, &mut vec).unwrap();
+    Endianness::Little.write(0x1234_u16, &mut vec).unwrap();
+    Endianness::NATIVE.write(0x1234_u16, &mut vec).unwrap();
+    assert_eq!(&vec[..4], &[0x12, 0x34, 0x34, 0x12]);
+    assert_eq!(&vec[4..], &0x1234_u16.to_ne_bytes());
+
+    let mut slice = vec.as_slice();
+    assert_eq!(Endianness::Big.read::<u16, _>(&mut slice).unwrap(), 0x1234);
+    assert_eq!(Endianness::Big.read::<u16, _>(&mut slice).unwrap(), 0x3412);
+}
@@ -0,0 +1,85 @@
+use endiannezz::Io;
+use std::io::ErrorKind;
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct Extension {
+    kind: u8,
+    value: u16,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct Record {
+    size: u8,
+    #[io(size = size)]
+    extension: Extension,
+    #[io(size = 2, trailing = error)]
+    tail: u16,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct Nested {
+    size: u8,
+    #[io(size = size + 1, count = size)]
+    items: Vec<u8>,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct Strict {
+    #[io(size = 4, trailing = error)]
+    extension: Extension,
+}
+
+#[test]
+fn sized_struct() {
+    let s1 = Record {
+        size: 3,
+        extension: Extension { kind: 1, value: 2 },
+        tail: 0,
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+    assert_eq!(vec, &[3, 1, 0, 2, 0, 0]);
+
+    //bytes appended by a newer version are skipped
+    let mut slice: &[u8] = &[5, 1, 0, 2, 0xaa, 0xbb, 0, 1];
+    let s2 = Record::read(&mut slice).unwrap();
+    assert_eq!(
+        s2,
+        Record {
+            size: 5,
+            tail: 1,
+            ..s1
+        }
+    );
+    assert!(slice.is_empty());
+
+    //the field can't read more than its size
+    let mut slice: &[u8] = &[2, 1, 0, 2, 0, 0];
+    let err = Record::read(&mut slice).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+    let mut slice: &[u8] = &[1, 0, 2, 0xaa];
+    let err = Strict::read(&mut slice).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(err.to_string(), "`extension` has 1 trailing bytes");
+
+    let mut slice: &[u8] = &[2, 7, 8, 0];
+    let s4 = Nested::read(&mut slice).unwrap();
+    assert_eq!(s4.items, &[7, 8]);
+    let err = s4.write(&mut Vec::new()).unwrap_err();
+    assert_eq!(err.to_string(), "`items` takes 2 bytes instead of 3");
+
+    let s3 = Record {
+        size: 4,
+        extension: Extension { kind: 1, value: 2 },
+        tail: 0,
+    };
+    let err = s3.write(&mut Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert_eq!(err.to_string(), "`extension` takes 3 bytes instead of 4");
+}
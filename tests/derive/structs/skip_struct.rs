@@ -0,0 +1,59 @@
+use endiannezz::Io;
+use std::marker::PhantomData;
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(little)]
+struct Cached<T> {
+    len: u16,
+    #[io(count = len)]
+    items: Vec<u8>,
+    #[io(skip = items.iter().map(|&item| u32::from(item)).sum())]
+    sum: u32,
+    #[io(skip)]
+    cache: Option<String>,
+    #[io(skip)]
+    marker: PhantomData<T>,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct Tuple(u8, #[io(skip = *field_0 * 2)] u8, u16);
+
+#[test]
+fn skip_struct() {
+    let s1: Cached<String> = Cached {
+        len: 2,
+        items: vec![3, 4],
+        sum: 0,
+        cache: Some(String::from("cache")),
+        marker: PhantomData,
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    assert_eq!(slice, &[2, 0, 3, 4]);
+
+    let s2 = Cached::read(&mut slice).unwrap();
+    assert_eq!(
+        s2,
+        Cached {
+            sum: 7,
+            cache: None,
+            ..s1
+        }
+    );
+}
+
+#[test]
+fn skip_tuple_struct() {
+    let s1 = Tuple(2, 0, 1);
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+    assert_eq!(vec, &[2, 0, 1]);
+
+    let s2 = Tuple::read(vec.as_slice()).unwrap();
+    assert_eq!(s2, Tuple(2, 4, 1));
+}
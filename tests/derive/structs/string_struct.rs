@@ -0,0 +1,67 @@
+use endiannezz::Io;
+use std::ffi::CString;
+use std::io::ErrorKind;
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(little)]
+struct ParseMe {
+    #[io(len = u16)]
+    title: String,
+    #[io(len = u8, len_endian = be)]
+    short: String,
+    #[io(cstr)]
+    path: String,
+    #[io(fixed = 6)]
+    name: String,
+    #[io(fixed = 4, pad = b' ')]
+    tag: String,
+    raw: CString,
+}
+
+#[test]
+fn string_struct() {
+    let s1 = ParseMe {
+        title: "hi".to_string(),
+        short: "é".to_string(),
+        path: "a/b".to_string(),
+        name: "abc".to_string(),
+        tag: "xy".to_string(),
+        raw: CString::new("c").unwrap(),
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    #[rustfmt::skip]
+    assert_eq!(slice, &[
+        2, 0, b'h', b'i',
+        2, 0xc3, 0xa9,
+        b'a', b'/', b'b', 0,
+        b'a', b'b', b'c', 0, 0, 0,
+        b'x', b'y', b' ', b' ',
+        b'c', 0,
+    ][..]);
+
+    let s2 = ParseMe::read(&mut slice).unwrap();
+    assert_eq!(s1, s2);
+}
+
+#[test]
+fn string_struct_errors() {
+    let mut invalid: &[u8] = &[1, 0, 0xff];
+    let err = ParseMe::read(&mut invalid).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert!(err.to_string().contains("UTF-8"));
+
+    let too_long = ParseMe {
+        title: String::new(),
+        short: String::new(),
+        path: String::new(),
+        name: "too long".to_string(),
+        tag: String::new(),
+        raw: CString::default(),
+    };
+    let err = too_long.write(Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
@@ -0,0 +1,48 @@
+use endiannezz::Io;
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(little)]
+struct Header {
+    id: u16,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct ParseMe(
+    (u8, u16),
+    #[endian(little)] (i32, (bool, u16)),
+    (Header, [u16; 2]),
+    (u8,),
+);
+
+#[test]
+fn tuple_field_struct() {
+    let s1 = ParseMe((1, 2), (-1, (true, 3)), (Header { id: 4 }, [5, 6]), (7,));
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    #[rustfmt::skip]
+    assert_eq!(slice, &[
+        1, 0, 2,
+        0xff, 0xff, 0xff, 0xff, 1, 3, 0,
+        4, 0, 0, 5, 0, 6,
+        7,
+    ]);
+
+    let s2 = ParseMe::read(&mut slice).unwrap();
+    assert_eq!(s1, s2);
+}
+
+#[test]
+fn tuple_io() {
+    let t1 = (Header { id: 1 }, true, [Header { id: 2 }]);
+
+    let mut vec = Vec::new();
+    t1.write(&mut vec).unwrap();
+    assert_eq!(vec, &[1, 0, 1, 2, 0]);
+
+    let t2 = <(Header, bool, [Header; 1])>::read(vec.as_slice()).unwrap();
+    assert_eq!(t1, t2);
+}
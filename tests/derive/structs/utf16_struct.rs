@@ -0,0 +1,51 @@
+use endiannezz::Io;
+use std::io::ErrorKind;
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(little)]
+struct ParseMe {
+    #[io(utf16, len = u16)]
+    name: String,
+    #[endian(big)]
+    #[io(utf16, cstr)]
+    path: String,
+    #[endian(big)]
+    #[io(utf16, len = u8, len_endian = le, lossy)]
+    broken: String,
+}
+
+#[test]
+fn utf16_struct() {
+    let s1 = ParseMe {
+        name: "A😀".to_string(),
+        path: "C:".to_string(),
+        broken: "ok".to_string(),
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    #[rustfmt::skip]
+    assert_eq!(slice, &[
+        3, 0, 0x41, 0, 0x3d, 0xd8, 0x00, 0xde,
+        0, 0x43, 0, 0x3a, 0, 0,
+        2, 0, 0x6f, 0, 0x6b,
+    ]);
+
+    let s2 = ParseMe::read(&mut slice).unwrap();
+    assert_eq!(s1, s2);
+}
+
+#[test]
+fn utf16_struct_surrogates() {
+    //lone high surrogate in the lossy field is replaced
+    let mut replaced: &[u8] = &[0, 0, 0, 0, 1, 0xd8, 0x3d];
+    let s = ParseMe::read(&mut replaced).unwrap();
+    assert_eq!(s.broken, "\u{fffd}");
+
+    //but rejected everywhere else
+    let mut rejected: &[u8] = &[1, 0, 0x3d, 0xd8];
+    let err = ParseMe::read(&mut rejected).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
@@ -0,0 +1,42 @@
+use endiannezz::Io;
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct ParseMe {
+    #[io(varint)]
+    length: u32,
+    #[io(varint)]
+    delta: i64,
+    #[io(varint = zigzag)]
+    offset: i32,
+    #[io(varint = vlq)]
+    ticks: u32,
+    fixed: u16,
+}
+
+#[test]
+fn varint_struct() {
+    let s1 = ParseMe {
+        length: 300,
+        delta: -123456,
+        offset: -2,
+        ticks: 0x3fff,
+        fixed: 1,
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    #[rustfmt::skip]
+    assert_eq!(slice, &[
+        0xac, 0x02,
+        0xc0, 0xbb, 0x78,
+        0x03,
+        0xff, 0x7f,
+        0, 1,
+    ]);
+
+    let s2 = ParseMe::read(&mut slice).unwrap();
+    assert_eq!(s1, s2);
+}
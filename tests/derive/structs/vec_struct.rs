@@ -0,0 +1,58 @@
+use endiannezz::Io;
+use std::io::ErrorKind;
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(little)]
+struct Record {
+    id: u16,
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(little)]
+struct ParseMe {
+    #[io(len = u8)]
+    bytes: Vec<u8>,
+    #[endian(big)]
+    #[io(len = u16)]
+    words: Vec<u16>,
+    #[io(len = u32, len_endian = be)]
+    records: Vec<Record>,
+}
+
+#[test]
+fn vec_struct() {
+    let s1 = ParseMe {
+        bytes: vec![0xde, 0xad],
+        words: vec![1, 2],
+        records: vec![Record { id: 3 }],
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    #[rustfmt::skip]
+    assert_eq!(slice, &[
+        2, 0xde, 0xad,
+        0, 2, 0, 1, 0, 2,
+        0, 0, 0, 1, 3, 0,
+    ]);
+
+    let s2 = ParseMe::read(&mut slice).unwrap();
+    assert_eq!(s1, s2);
+
+    let mut truncated: &[u8] = &[3, 0xde, 0xad];
+    assert!(ParseMe::read(&mut truncated).is_err());
+}
+
+#[test]
+fn vec_struct_overflow() {
+    let s1 = ParseMe {
+        bytes: vec![0; 256],
+        words: vec![],
+        records: vec![],
+    };
+
+    let err = s1.write(Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
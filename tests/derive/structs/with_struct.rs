@@ -0,0 +1,65 @@
+use endiannezz::{Endian, Io};
+use std::io::{Read, Result, Write};
+use std::net::Ipv4Addr;
+use std::time::{Duration, UNIX_EPOCH};
+
+mod ipv4 {
+    use super::*;
+
+    pub fn write<E: Endian, W: Write>(addr: &Ipv4Addr, w: W) -> Result<()> {
+        E::write(u32::from(*addr), w)
+    }
+
+    pub fn read<E: Endian, R: Read>(r: R) -> Result<Ipv4Addr> {
+        Ok(Ipv4Addr::from(E::read::<u32, _>(r)?))
+    }
+}
+
+mod unix_time {
+    use super::*;
+    use std::time::SystemTime;
+
+    pub fn write<E: Endian, W: Write>(time: &SystemTime, w: W) -> Result<()> {
+        let secs = time.duration_since(UNIX_EPOCH).unwrap().as_secs();
+        E::write(secs, w)
+    }
+
+    pub fn read<E: Endian, R: Read>(r: R) -> Result<SystemTime> {
+        Ok(UNIX_EPOCH + Duration::from_secs(E::read(r)?))
+    }
+}
+
+#[derive(Io, Debug, PartialEq)]
+#[endian(big)]
+struct Packet {
+    #[io(with = ipv4)]
+    source: Ipv4Addr,
+    #[io(with = self::ipv4)]
+    #[endian(little)]
+    destination: Ipv4Addr,
+    #[io(with = unix_time)]
+    time: std::time::SystemTime,
+}
+
+#[test]
+fn with_struct() {
+    let s1 = Packet {
+        source: Ipv4Addr::new(127, 0, 0, 1),
+        destination: Ipv4Addr::new(10, 0, 0, 2),
+        time: UNIX_EPOCH + Duration::from_secs(3),
+    };
+
+    let mut vec = Vec::new();
+    s1.write(&mut vec).unwrap();
+
+    let mut slice = vec.as_slice();
+    #[rustfmt::skip]
+    assert_eq!(slice, &[
+        127, 0, 0, 1,
+        2, 0, 0, 10,
+        0, 0, 0, 0, 0, 0, 0, 3,
+    ]);
+
+    let s2 = Packet::read(&mut slice).unwrap();
+    assert_eq!(s1, s2);
+}
@@ -0,0 +1,92 @@
+use endiannezz::bits::{BitReader, BitWriter, LsbFirst, MsbFirst};
+use endiannezz::ext::{EndianReader, EndianWriter};
+use std::io::ErrorKind;
+
+#[test]
+fn msb_first() {
+    let mut writer = BitWriter::<_, MsbFirst>::new(Vec::new());
+    writer.write_bits(0b101_u8, 3).unwrap();
+    writer.write_bits(-3_i8, 5).unwrap();
+    writer.write_bit(true).unwrap();
+    writer.align().unwrap();
+    writer.write_be::<u16>(0x1234).unwrap();
+    writer.write_bits(0x7_u8, 4).unwrap();
+    writer.write_be::<u8>(0xab).unwrap();
+    let vec = writer.into_inner().unwrap();
+
+    assert_eq!(vec, &[0b1011_1101, 0b1000_0000, 0x12, 0x34, 0x7a, 0xb0]);
+
+    let mut reader = BitReader::<_, MsbFirst>::new(vec.as_slice());
+    assert_eq!(reader.read_bits::<u8>(3).unwrap(), 0b101);
+    assert_eq!(reader.read_bits::<i8>(5).unwrap(), -3);
+    assert!(reader.read_bit().unwrap());
+    assert!(!reader.is_aligned());
+    reader.align();
+    assert_eq!(reader.read_be::<u16>().unwrap(), 0x1234);
+    assert_eq!(reader.read_bits::<u32>(4).unwrap(), 0x7);
+    assert_eq!(reader.read_be::<u8>().unwrap(), 0xab);
+}
+
+#[test]
+fn lsb_first() {
+    let mut writer = BitWriter::<_, LsbFirst>::new(Vec::new());
+    writer.write_bits(0b101_u8, 3).unwrap();
+    writer.write_bits(0b11001_u16, 5).unwrap();
+    writer.write_bits(0x3ff_u16, 10).unwrap();
+    let vec = writer.into_inner().unwrap();
+
+    assert_eq!(vec, &[0b1100_1101, 0xff, 0b11]);
+
+    let mut reader = BitReader::<_, LsbFirst>::new(vec.as_slice());
+    assert_eq!(reader.read_bits::<u8>(3).unwrap(), 0b101);
+    assert_eq!(reader.read_bits::<u8>(5).unwrap(), 0b11001);
+    assert_eq!(reader.read_bits::<u16>(10).unwrap(), 0x3ff);
+}
+
+#[test]
+fn exp_golomb() {
+    let mut writer = BitWriter::<_, MsbFirst>::new(Vec::new());
+    for value in &[0, 1, 2, 3, 7, u32::MAX] {
+        writer.write_exp_golomb(*value).unwrap();
+    }
+    for value in &[0, 1, -1, 2, -2, i32::MAX, i32::MIN + 1] {
+        writer.write_signed_exp_golomb(*value).unwrap();
+    }
+    let err = writer.write_signed_exp_golomb(i32::MIN).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    let vec = writer.into_inner().unwrap();
+
+    //1, 010, 011, 00100, 0001000
+    assert_eq!(&vec[..3], &[0b1010_0110, 0b0100_0001, 0b000_00000]);
+
+    let mut reader = BitReader::<_, MsbFirst>::new(vec.as_slice());
+    for value in &[0, 1, 2, 3, 7, u32::MAX] {
+        assert_eq!(reader.read_exp_golomb().unwrap(), *value);
+    }
+    for value in &[0, 1, -1, 2, -2, i32::MAX, i32::MIN + 1] {
+        assert_eq!(reader.read_signed_exp_golomb().unwrap(), *value);
+    }
+}
+
+#[test]
+fn out_of_range() {
+    let mut writer = BitWriter::<_, MsbFirst>::new(Vec::new());
+    let err = writer.write_bits(8_u8, 3).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    let err = writer.write_bits(-5_i8, 3).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    let err = writer.write_bits(0_u8, 9).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+    let mut reader = BitReader::<_, MsbFirst>::new(&[0xff_u8][..]);
+    let err = reader.read_bits::<u16>(9).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+    //the largest code maps to 2^31, which doesn't fit into i32
+    let mut writer = BitWriter::<_, MsbFirst>::new(Vec::new());
+    writer.write_exp_golomb(u32::MAX).unwrap();
+    let vec = writer.into_inner().unwrap();
+    let mut reader = BitReader::<_, MsbFirst>::new(vec.as_slice());
+    let err = reader.read_signed_exp_golomb().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
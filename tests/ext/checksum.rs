@@ -0,0 +1,48 @@
+use endiannezz::checksum::{
+    Adler32, Checksum, ChecksumReader, ChecksumWriter, Crc32, Sum16, Sum32, Sum8,
+};
+use std::io::{Read, Write};
+
+fn digest<C: Checksum + Default>(bytes: &[u8]) -> C::Output {
+    let mut checksum = C::default();
+    checksum.update(bytes);
+    checksum.finish()
+}
+
+#[test]
+fn algorithms() {
+    let data = b"123456789";
+    assert_eq!(digest::<Crc32>(data), 0xcbf4_3926);
+    assert_eq!(digest::<Adler32>(data), 0x091e_01de);
+    assert_eq!(digest::<Sum8>(data), 0xdd);
+    assert_eq!(digest::<Sum16>(data), 0x01dd);
+    assert_eq!(digest::<Sum32>(data), 0x01dd);
+
+    assert_eq!(digest::<Crc32>(b""), 0);
+    assert_eq!(digest::<Adler32>(b""), 1);
+
+    //adler32 reduces its sums in chunks, so long input checks the overflow handling
+    let long = vec![0xff; 100_000];
+    let mut checksum = Adler32::default();
+    for chunk in long.chunks(7) {
+        checksum.update(chunk);
+    }
+    assert_eq!(checksum.finish(), digest::<Adler32>(&long));
+    assert_eq!(checksum.finish(), 0x149a_302c);
+}
+
+#[test]
+fn adapters() {
+    let mut writer = ChecksumWriter::<_, Crc32>::new(Vec::new());
+    writer.write_all(b"1234").unwrap();
+    writer.write_all(b"56789").unwrap();
+    assert_eq!(writer.checksum(), 0xcbf4_3926);
+    let vec = writer.into_inner();
+
+    let mut state = Sum8::default();
+    let mut reader = ChecksumReader::with_checksum(vec.as_slice(), &mut state);
+    let mut buf = [0; 9];
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(reader.checksum(), 0xdd);
+    assert_eq!(state.finish(), 0xdd);
+}
@@ -0,0 +1 @@
+automod::dir!("tests/ext");
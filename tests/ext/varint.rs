@@ -0,0 +1,41 @@
+use endiannezz::varint::{VarintReader, VarintWriter};
+use std::io::ErrorKind;
+
+#[test]
+fn extremes() {
+    let mut vec = Vec::new();
+    vec.write_uleb128(u64::MAX).unwrap();
+    vec.write_sleb128(i64::MIN).unwrap();
+    vec.write_zigzag(i128::MIN).unwrap();
+    vec.write_sleb128(i128::MAX).unwrap();
+    vec.write_vlq(u128::MAX).unwrap();
+
+    let mut slice = vec.as_slice();
+    assert_eq!(slice.read_uleb128::<u64>().unwrap(), u64::MAX);
+    assert_eq!(slice.read_sleb128::<i64>().unwrap(), i64::MIN);
+    assert_eq!(slice.read_zigzag::<i128>().unwrap(), i128::MIN);
+    assert_eq!(slice.read_sleb128::<i128>().unwrap(), i128::MAX);
+    assert_eq!(slice.read_vlq::<u128>().unwrap(), u128::MAX);
+    assert!(slice.is_empty());
+}
+
+#[test]
+fn malformed() {
+    //11 bytes can't be a valid u64
+    let mut too_long: &[u8] = &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+    let err = too_long.read_uleb128::<u64>().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    //fits into the max length, but not into the type
+    let mut overflow: &[u8] = &[0x80, 0x02];
+    let err = overflow.read_uleb128::<u8>().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    let mut overflow: &[u8] = &[0xff, 0xff, 0xff, 0xff, 0x7f];
+    let err = overflow.read_vlq::<u16>().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    let mut truncated: &[u8] = &[0x80];
+    let err = truncated.read_sleb128::<i32>().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+}
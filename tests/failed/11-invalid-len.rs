@@ -0,0 +1,24 @@
+use endiannezz::Io;
+
+#[derive(Io)]
+#[endian(big)]
+struct Foo {
+    #[io(len = u16)]
+    a: [u8; 4],
+}
+
+#[derive(Io)]
+#[endian(big)]
+struct Bar {
+    #[io(len_endian = le)]
+    a: Vec<u8>,
+}
+
+#[derive(Io)]
+#[endian(big)]
+struct Baz {
+    #[io(len = u16, len = u32)]
+    a: Vec<u8>,
+}
+
+fn main() {}
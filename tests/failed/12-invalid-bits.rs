@@ -0,0 +1,37 @@
+use endiannezz::Io;
+
+#[derive(Io)]
+#[endian(big)]
+struct Foo {
+    #[bits(4)]
+    a: u8,
+}
+
+#[derive(Io)]
+#[endian(big)]
+#[io(bitfield = u8)]
+struct Bar {
+    #[bits(4)]
+    a: u8,
+    #[bits(6)]
+    b: u8,
+}
+
+#[derive(Io)]
+#[endian(big)]
+#[io(bitfield = u8)]
+struct Baz {
+    #[bits(4)]
+    a: u8,
+    b: u16,
+}
+
+#[derive(Io)]
+#[endian(big)]
+#[io(bitfield = i16)]
+struct Qux {
+    #[bits(16)]
+    a: u16,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use endiannezz::Io;
+
+#[derive(Io)]
+#[endian(runtime)]
+struct Foo {
+    #[endian(runtime)]
+    a: u32,
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+use endiannezz::Io;
+
+#[derive(Io)]
+#[endian(from = ::endiannezz::Endianness::Little)]
+struct Foo {
+    a: u32,
+}
+
+#[derive(Io)]
+#[endian(from = a)]
+#[repr(u8)]
+enum Bar {
+    A = 1,
+}
+
+#[derive(Io)]
+#[endian(to = a)]
+struct Baz {
+    a: u32,
+}
+
+fn main() {}
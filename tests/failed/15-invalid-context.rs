@@ -0,0 +1,18 @@
+use endiannezz::Io;
+
+#[derive(Io)]
+#[endian(runtime)]
+#[io(context(size: u16))]
+struct Foo {
+    #[io(count = size)]
+    a: Vec<u8>,
+}
+
+#[derive(Io)]
+#[endian(big)]
+struct Bar {
+    #[io(cstr, args(1))]
+    a: String,
+}
+
+fn main() {}
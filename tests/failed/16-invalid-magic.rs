@@ -0,0 +1,24 @@
+use endiannezz::Io;
+
+#[derive(Io)]
+#[endian(big)]
+#[io(magic = 0xCAFEBABE)]
+struct Foo {
+    a: u32,
+}
+
+#[derive(Io)]
+#[endian(from = mark)]
+#[io(magic = 1_u16)]
+struct Bar {
+    mark: endiannezz::Endianness,
+}
+
+#[derive(Io)]
+#[endian(big)]
+#[io(magic = "text")]
+struct Baz {
+    a: u32,
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use endiannezz::Io;
+
+#[derive(Io)]
+#[endian(big)]
+struct Foo {
+    #[io(skip, len = u8)]
+    a: Vec<u8>,
+}
+
+#[derive(Io)]
+#[endian(big)]
+struct Bar {
+    #[io(skip)]
+    #[endian(little)]
+    a: u32,
+}
+
+fn main() {}
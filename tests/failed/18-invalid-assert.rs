@@ -0,0 +1,11 @@
+use endiannezz::Io;
+
+#[derive(Io)]
+#[endian(big)]
+#[io(assert = true)]
+#[repr(u8)]
+enum Foo {
+    A = 1,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use endiannezz::Io;
+
+#[derive(Io)]
+#[endian(big)]
+struct Foo {
+    #[io(map = (u64::from, |v: &u64| *v as u32))]
+    a: u64,
+}
+
+#[derive(Io)]
+#[endian(big)]
+struct Bar {
+    #[io(wire = u32, map = u64::from)]
+    a: u64,
+}
+
+fn main() {}
@@ -0,0 +1,27 @@
+use endiannezz::Io;
+
+#[derive(Io)]
+#[endian(big)]
+struct Foo {
+    a: u32,
+    #[io(checksum = crc32, over = "a..=b")]
+    crc: u32,
+    b: u32,
+}
+
+#[derive(Io)]
+#[endian(big)]
+struct Bar {
+    a: u32,
+    #[io(checksum = crc32, over = "a..c")]
+    crc: u32,
+}
+
+#[derive(Io)]
+#[endian(big)]
+struct Baz {
+    #[io(over = "..")]
+    a: u32,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use endiannezz::Io;
+
+#[derive(Io)]
+#[endian(big)]
+struct Foo {
+    #[io(trailing = error)]
+    a: u32,
+}
+
+#[derive(Io)]
+#[endian(big)]
+struct Bar {
+    #[io(size = 4, trailing = ignore)]
+    a: u32,
+}
+
+fn main() {}
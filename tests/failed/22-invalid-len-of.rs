@@ -0,0 +1,27 @@
+use endiannezz::Io;
+
+#[derive(Io)]
+#[endian(big)]
+struct Foo {
+    a: u32,
+    #[io(len_of = a)]
+    len: u16,
+}
+
+#[derive(Io)]
+#[endian(big)]
+struct Bar {
+    #[io(len_of = a, calc = 4)]
+    len: u16,
+    a: u32,
+}
+
+#[derive(Io)]
+#[endian(big)]
+struct Baz {
+    #[io(len_of = a, varint)]
+    len: u16,
+    a: u32,
+}
+
+fn main() {}
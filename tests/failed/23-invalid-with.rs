@@ -0,0 +1,38 @@
+use endiannezz::Io;
+
+mod codec {
+    use endiannezz::Endian;
+    use std::io::{Read, Result, Write};
+
+    pub fn write<E: Endian, W: Write>(value: &u32, w: W) -> Result<()> {
+        E::write(*value, w)
+    }
+
+    pub fn read<E: Endian, R: Read>(r: R) -> Result<u32> {
+        E::read(r)
+    }
+}
+
+#[derive(Io)]
+#[endian(big)]
+struct Foo {
+    #[io(with = codec, varint)]
+    a: u32,
+}
+
+#[derive(Io)]
+#[endian(big)]
+struct Bar {
+    flag: bool,
+    #[io(with = codec, if = *flag)]
+    a: Option<u32>,
+}
+
+#[derive(Io)]
+#[endian(big)]
+struct Baz {
+    #[io(args(1), with = codec)]
+    a: u32,
+}
+
+fn main() {}
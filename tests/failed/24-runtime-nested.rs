@@ -0,0 +1,17 @@
+use endiannezz::Io;
+
+#[derive(Io)]
+#[endian(runtime)]
+struct Header {
+    a: u32,
+}
+
+#[derive(Io)]
+#[endian(big)]
+struct Foo {
+    header: Header,
+}
+
+fn main() {
+    Header { a: 0 }.write(Vec::new()).unwrap();
+}
@@ -0,0 +1,35 @@
+use endiannezz::Io;
+
+#[derive(Io)]
+#[endian(from = match order {
+    b"II" => Some(endiannezz::Endianness::Little),
+    b"MM" => Some(endiannezz::Endianness::Big),
+    _ => None,
+})]
+struct Foo {
+    size: u32,
+    order: [u8; 2],
+}
+
+#[derive(Io)]
+#[endian(from = match order {
+    0x4949 => Some(endiannezz::Endianness::Little),
+    0x4d4d => Some(endiannezz::Endianness::Big),
+    _ => None,
+})]
+struct Bar {
+    order: u16,
+}
+
+#[derive(Io)]
+#[endian(from = match order {
+    b"II" => Some(endiannezz::Endianness::Little),
+    _ => None,
+})]
+struct Baz {
+    #[io(len = u16)]
+    name: String,
+    order: [u8; 2],
+}
+
+fn main() {}
@@ -1,3 +1,4 @@
 #![cfg_attr(unstable_feature, feature(arbitrary_enum_discriminant))]
 
 mod derive;
+mod ext;